@@ -1,36 +1,37 @@
 use sursface::app::AppState;
 use sursface::cgmath::{perspective, Deg, Matrix4, Point3, SquareMatrix, Vector3};
-use sursface::display::Display;
+use sursface::display::{Display, DisplayConfig};
 use sursface::std::models::{cube, quad_uvs, VertexPositionNormalUv};
 use sursface::std::{
     clear, create_render_pipeline, create_sampler_entry, create_shader, create_texture,
-    create_texture_layout_entry_from_image, create_uniforms, get_framebuffer,
+    create_texture_layout_entry_from_image, create_uniforms,
 };
-use sursface::time::now_secs;
+use sursface::time::FrameClock;
 use sursface::wgpu::util::{BufferInitDescriptor, DeviceExt};
 use sursface::wgpu::{
     BindGroup, BindGroupEntry, BindingResource, Buffer, BufferAddress, BufferUsages, Color,
-    CommandEncoderDescriptor, PipelineLayoutDescriptor, RenderPipeline, VertexAttribute,
-    VertexBufferLayout, VertexFormat, VertexStepMode,
+    CommandEncoderDescriptor, PipelineLayoutDescriptor, RenderPipeline, TextureFormat,
+    VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode,
 };
 use sursface::winit::event::WindowEvent;
 
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        sursface::start::create_window_desktop::<CubeState>(1280, 720);
+        if sursface::start::create_window_desktop::<CubeState>(1280, 720).is_err() {
+            std::process::exit(1);
+        }
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 pub fn start_browser(canvas: sursface::wgpu::web_sys::HtmlCanvasElement) {
-    sursface::start::create_window_browser::<CubeState>(canvas);
+    let _ = sursface::start::create_window_browser::<CubeState>(canvas);
 }
 
 struct CubeState {
     render_pipeline: RenderPipeline,
-    start_time: f32,
     uniform_buffer: Buffer,
     uniform_bind_group: BindGroup,
     vertex_buffer: Buffer,
@@ -49,6 +50,12 @@ pub struct Uniforms {
 }
 
 impl AppState for CubeState {
+    fn display_config() -> DisplayConfig {
+        DisplayConfig::default()
+            .with_depth_format(TextureFormat::Depth32Float)
+            .with_sample_count(4)
+    }
+
     fn new(display: &mut Display) -> CubeState {
         let device = &display.device;
 
@@ -94,6 +101,8 @@ impl AppState for CubeState {
 
         let render_pipeline = create_render_pipeline(
             display,
+            display.config.format,
+            true,
             pipeline_layout,
             shader,
             &[VertexBufferLayout {
@@ -119,8 +128,6 @@ impl AppState for CubeState {
             }],
         );
 
-        let start_time = now_secs();
-
         let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: None,
             contents: bytemuck::cast_slice(&cube(&[
@@ -129,14 +136,13 @@ impl AppState for CubeState {
                 quad_uvs((0.25, 1f32 / 3f32), (0.25 + 0.25, 2f32 / 3f32)), // 4
                 quad_uvs((0.75, 1f32 / 3f32), (0.25 + 0.75, 2f32 / 3f32)), // 3
                 quad_uvs((0.50, 0f32 / 3f32), (0.25 + 0.50, 1f32 / 3f32)), // 2
-                quad_uvs((0.50, 2f32 / 3f32), (0.25 + 0.50, 3f32 / 3f32)), // 5
+                quad_uvs((0.50, 2f32 / 3f32), (0.25 + 0.50, 1f32)), // 5
             ])),
             usage: BufferUsages::VERTEX,
         });
 
         CubeState {
             render_pipeline,
-            start_time,
             uniform_buffer,
             uniform_bind_group,
             vertex_buffer,
@@ -151,12 +157,12 @@ impl AppState for CubeState {
         }
     }
 
-    fn draw(&mut self, display: &mut Display) {
+    fn draw(&mut self, display: &mut Display, clock: &mut FrameClock) {
         let clear_color = Color {
             r: 252.0 / 255.0,
             g: 241.0 / 255.0,
             b: 139.0 / 255.0,
-            a: 255.0 / 255.0,
+            a: 1.0,
         };
 
         let output = {
@@ -166,13 +172,20 @@ impl AppState for CubeState {
                     label: None,
                 });
 
-            let (output, view) = get_framebuffer(&display.surface);
+            let Some(output) = display.get_framebuffer() else {
+                return;
+            };
             {
-                let mut rpass = clear(&view, &mut encoder, clear_color);
+                let mut rpass = clear(
+                    display,
+                    &output.view,
+                    output.format,
+                    true,
+                    &mut encoder,
+                    clear_color,
+                );
 
-                let now = now_secs();
-                let elapsed = now - self.start_time;
-                sursface::log::info!("{} {}", now, elapsed);
+                sursface::log::info!("{} {}", clock.frame(), clock.total());
                 let aspect_ratio = display.config.width as f32 / display.config.height as f32;
 
                 let model = Matrix4::identity();
@@ -1,19 +1,22 @@
 use sursface::app::AppState;
 use sursface::display::Display;
-use sursface::std::{clear, create_render_pipeline, create_shader, get_framebuffer};
+use sursface::std::{clear, create_render_pipeline, create_shader};
+use sursface::time::FrameClock;
 use sursface::wgpu::{self, Color, RenderPipeline};
 
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        sursface::start::create_window_desktop::<TriangleState>(1280, 720);
+        if sursface::start::create_window_desktop::<TriangleState>(1280, 720).is_err() {
+            std::process::exit(1);
+        }
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 pub fn start_browser(canvas: sursface::wgpu::web_sys::HtmlCanvasElement) {
-    sursface::start::create_window_browser::<TriangleState>(canvas);
+    let _ = sursface::start::create_window_browser::<TriangleState>(canvas);
 }
 
 struct TriangleState {
@@ -32,16 +35,23 @@ impl AppState for TriangleState {
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = create_render_pipeline(display, pipeline_layout, shader, &[]);
+        let render_pipeline = create_render_pipeline(
+            display,
+            display.config.format,
+            false,
+            pipeline_layout,
+            shader,
+            &[],
+        );
         TriangleState { render_pipeline }
     }
 
-    fn draw(&mut self, display: &mut Display) {
+    fn draw(&mut self, display: &mut Display, _clock: &mut FrameClock) {
         let clear_color = Color {
             r: 100.0 / 255.0,
             g: 149.0 / 255.0,
             b: 237.0 / 255.0,
-            a: 255.0 / 255.0,
+            a: 1.0,
         };
 
         let device = &display.device;
@@ -50,9 +60,18 @@ impl AppState for TriangleState {
             label: None,
         });
 
-        let (output, view) = get_framebuffer(&display.surface);
+        let Some(output) = display.get_framebuffer() else {
+            return;
+        };
         {
-            let mut rpass = clear(&view, &mut encoder, clear_color);
+            let mut rpass = clear(
+                display,
+                &output.view,
+                output.format,
+                false,
+                &mut encoder,
+                clear_color,
+            );
 
             rpass.set_pipeline(&self.render_pipeline);
             rpass.draw(0..3, 0..1);
@@ -1,18 +1,21 @@
 use sursface::app::AppState;
 use sursface::display::Display;
+use sursface::time::FrameClock;
 use sursface::wgpu::{self, TextureView};
 
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        sursface::start::create_window_desktop::<EmptyState>(1280, 720);
+        if sursface::start::create_window_desktop::<EmptyState>(1280, 720).is_err() {
+            std::process::exit(1);
+        }
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 pub fn start_browser(canvas: sursface::wgpu::web_sys::HtmlCanvasElement) {
-    sursface::start::create_window_browser::<EmptyState>(canvas);
+    let _ = sursface::start::create_window_browser::<EmptyState>(canvas);
 }
 
 #[derive(Clone)]
@@ -23,15 +26,14 @@ impl AppState for EmptyState {
         EmptyState {}
     }
 
-    fn draw<'a>(&mut self, display: &mut Display) {
-        let output = display.surface.get_current_texture().unwrap();
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    fn draw<'a>(&mut self, display: &mut Display, _clock: &mut FrameClock) {
+        let Some(output) = display.get_framebuffer() else {
+            return;
+        };
 
         clear(
             display,
-            &view,
+            &output.view,
             wgpu::Color {
                 r: 100.0 / 255.0,
                 g: 149.0 / 255.0,
@@ -44,7 +46,7 @@ impl AppState for EmptyState {
     }
 }
 
-fn clear<'a>(display: &mut Display, view: &TextureView, color: sursface::wgpu::Color) {
+fn clear(display: &mut Display, view: &TextureView, color: sursface::wgpu::Color) {
     let mut encoder = display
         .device
         .create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -55,7 +57,7 @@ fn clear<'a>(display: &mut Display, view: &TextureView, color: sursface::wgpu::C
         let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
+                view,
                 resolve_target: None,
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(color),
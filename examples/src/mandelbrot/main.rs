@@ -1,13 +1,14 @@
 use bytemuck::{Pod, Zeroable};
-use std::fmt::Display as FmtDisplay;
 use sursface::app::AppState;
 use sursface::cgmath::{Vector2, Zero};
 use sursface::display::Display;
+use sursface::input::{ActionMap, AxisBinding, Binding, Gesture, GestureConfig, GestureRecognizer};
 use sursface::std::models::{quad_no_normal, quad_uvs, VertexPositionUv};
 use sursface::std::{
-    clear, create_render_pipeline, create_shader, create_uniforms, get_framebuffer,
+    clear, create_render_pipeline, create_shader, create_uniforms,
 };
-use sursface::time::now_secs;
+use sursface::time::FrameClock;
+use sursface::wgpu;
 use sursface::wgpu::util::DeviceExt;
 use sursface::wgpu::{
     BindGroup, Buffer, BufferAddress, BufferUsages, Color, CommandEncoderDescriptor,
@@ -15,65 +16,28 @@ use sursface::wgpu::{
     VertexStepMode,
 };
 use sursface::winit::dpi::PhysicalPosition;
-use sursface::winit::event::{ElementState, MouseButton, WindowEvent};
-use sursface::{log, wgpu};
+use sursface::winit::event::WindowEvent;
+use sursface::winit::keyboard::KeyCode;
 
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        sursface::start::create_window_desktop::<MandelbrotState>(720, 720);
+        if sursface::start::create_window_desktop::<MandelbrotState>(720, 720).is_err() {
+            std::process::exit(1);
+        }
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen::prelude::wasm_bindgen]
 pub fn start_browser(canvas: sursface::wgpu::web_sys::HtmlCanvasElement) {
-    sursface::start::create_window_browser::<MandelbrotState>(canvas);
+    let _ = sursface::start::create_window_browser::<MandelbrotState>(canvas);
 }
 
-#[derive(Clone)]
-enum InteractionState {
-    Idle {
-        last_pressed_down_at: f32,
-        pre_tap: bool,
-    },
-    PanningIdle {
-        pressed_down_at: f32,
-        pre_tap: bool,
-    },
-    Panning,
-    Zooming,
-    ZoomingOut,
-}
-
-impl FmtDisplay for InteractionState {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            InteractionState::Idle {
-                last_pressed_down_at,
-                pre_tap,
-            } => {
-                write!(
-                    f,
-                    "Idle {{ last_pressed_down_at: {}, pre_tap: {} }}",
-                    last_pressed_down_at, pre_tap
-                )
-            }
-            InteractionState::PanningIdle {
-                pressed_down_at,
-                pre_tap,
-            } => {
-                write!(
-                    f,
-                    "PanningIdle {{ pressed_down_at: {}, pre_tap: {} }}",
-                    pressed_down_at, pre_tap
-                )
-            }
-            InteractionState::Panning => write!(f, "Panning"),
-            InteractionState::Zooming => write!(f, "Zooming"),
-            InteractionState::ZoomingOut => write!(f, "ZoomingOut"),
-        }
-    }
+#[derive(Clone, Copy)]
+enum Zoom {
+    In,
+    Out,
 }
 
 struct MandelbrotState {
@@ -83,10 +47,8 @@ struct MandelbrotState {
     uniform_bind_group: BindGroup,
     uniforms: Uniforms,
     scale_speed: f32,
-    last_cursor_location: PhysicalPosition<f32>,
-    cursor_location: PhysicalPosition<f32>,
-    last_timestep: f32,
-    interaction_state: InteractionState,
+    gestures: GestureRecognizer,
+    zoom: Option<Zoom>,
 }
 
 #[repr(C)]
@@ -100,6 +62,26 @@ pub struct Uniforms {
 }
 
 impl AppState for MandelbrotState {
+    fn actions() -> ActionMap {
+        ActionMap::default()
+            .with_action("zoom_in", Binding::Key(KeyCode::Equal))
+            .with_action("zoom_out", Binding::Key(KeyCode::Minus))
+            .with_axis(
+                "pan_x",
+                AxisBinding::Buttons {
+                    negative: Binding::Key(KeyCode::ArrowLeft),
+                    positive: Binding::Key(KeyCode::ArrowRight),
+                },
+            )
+            .with_axis(
+                "pan_y",
+                AxisBinding::Buttons {
+                    negative: Binding::Key(KeyCode::ArrowDown),
+                    positive: Binding::Key(KeyCode::ArrowUp),
+                },
+            )
+    }
+
     fn new(display: &mut Display) -> MandelbrotState {
         let device = &display.device;
         let aspect_ratio = display.config.width as f32 / display.config.height as f32;
@@ -125,6 +107,8 @@ impl AppState for MandelbrotState {
 
         let render_pipeline = create_render_pipeline(
             display,
+            display.config.format,
+            true,
             pipeline_layout,
             shader,
             &[VertexBufferLayout {
@@ -172,69 +156,60 @@ impl AppState for MandelbrotState {
                 _padding: [0.0; 2],
             },
             scale_speed: 1.0 - 0.001,
-            last_cursor_location: PhysicalPosition::new(0.0, 0.0),
-            cursor_location: PhysicalPosition::new(0.0, 0.0),
-            last_timestep: now_secs(),
-            interaction_state: InteractionState::Idle {
-                last_pressed_down_at: 0.0,
-                pre_tap: false,
-            },
+            // Hold to zoom in, tap and then hold to zoom out
+            gestures: GestureRecognizer::new(
+                GestureConfig::default()
+                    .with_long_press_duration(1.0)
+                    .with_double_tap_interval(1.0),
+            ),
+            zoom: None,
         }
     }
 
-    fn draw(&mut self, display: &mut Display) {
-        let dt = {
-            let mut dt = 0f32;
-            dt += now_secs() - self.last_timestep;
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                dt *= -1000f32; // hack
+    fn update(&mut self, display: &mut Display, clock: &mut FrameClock) {
+        for gesture in self.gestures.poll() {
+            match gesture {
+                Gesture::LongPress { after_tap, .. } => {
+                    self.zoom = Some(if after_tap { Zoom::Out } else { Zoom::In });
+                }
+                Gesture::LongPressEnd { .. } => self.zoom = None,
+                Gesture::Drag { delta, .. } => {
+                    let delta = display.to_uv(PhysicalPosition::new(delta.x, delta.y));
+                    self.uniforms.translation[0] -= delta.x * self.uniforms.scale;
+                    self.uniforms.translation[1] += delta.y * self.uniforms.scale;
+                }
+                Gesture::Pinch { scale, .. } => self.uniforms.scale /= scale as f32,
+                _ => (),
             }
+        }
 
-            dt
-        };
+        // scale_speed is the zoom per millisecond
+        let zoom = self.scale_speed.powf(clock.fixed_dt() as f32 * 1000.0);
+        let actions = &display.actions;
+        match self.zoom {
+            Some(Zoom::In) => self.uniforms.scale *= zoom,
+            Some(Zoom::Out) => self.uniforms.scale /= zoom,
+            None if actions.held("zoom_in") => self.uniforms.scale *= zoom,
+            None if actions.held("zoom_out") => self.uniforms.scale /= zoom,
+            None => (),
+        }
+
+        // Keys pan by half the view per second
+        let pan = 0.5 * clock.fixed_dt() as f32 * self.uniforms.scale;
+        self.uniforms.translation[0] += actions.axis("pan_x") * pan;
+        self.uniforms.translation[1] += actions.axis("pan_y") * pan;
+    }
 
-        self.last_timestep = now_secs();
+    fn draw(&mut self, display: &mut Display, _clock: &mut FrameClock) {
         self.uniforms.aspect_ratio = display.config.width as f32 / display.config.height as f32;
 
         let clear_color = Color {
             r: 100.0 / 255.0,
             g: 149.0 / 255.0,
             b: 237.0 / 255.0,
-            a: 255.0 / 255.0,
-        };
-
-        self.interaction_state = match self.interaction_state.clone() {
-            InteractionState::PanningIdle {
-                pressed_down_at,
-                pre_tap,
-            } => {
-                log::info!("{} {}", now_secs(), pressed_down_at);
-                if now_secs() - pressed_down_at > 1f32 {
-                    log::info!("zooming {}", now_secs());
-                    InteractionState::Zooming
-                } else {
-                    InteractionState::PanningIdle {
-                        pressed_down_at,
-                        pre_tap,
-                    }
-                }
-            }
-            state => state,
+            a: 1.0,
         };
 
-        match self.interaction_state {
-            InteractionState::Zooming => {
-                self.uniforms.scale *= self.scale_speed.powf(1f32 - dt as f32);
-            }
-            InteractionState::ZoomingOut => {
-                log::info!("zooming out {}", self.scale_speed.powf(1f32 - dt as f32));
-                self.uniforms.scale /= self.scale_speed.powf(1f32 - dt as f32);
-            }
-            _ => (),
-        }
-
         let output = {
             let mut encoder = display
                 .device
@@ -242,15 +217,23 @@ impl AppState for MandelbrotState {
                     label: None,
                 });
 
-            let (output, view) = get_framebuffer(&display.surface);
+            let Some(output) = display.get_framebuffer() else {
+                return;
+            };
 
             {
-                let mut rpass = clear(&view, &mut encoder, clear_color);
+                let mut rpass = clear(
+                    display,
+                    &output.view,
+                    output.format,
+                    true,
+                    &mut encoder,
+                    clear_color,
+                );
 
-                self.uniforms.cursor_pos = [
-                    self.cursor_location.x / display.config.width as f32,
-                    self.cursor_location.y / display.config.height as f32,
-                ];
+                if let Some(cursor) = display.input.cursor_position() {
+                    self.uniforms.cursor_pos = display.to_uv(cursor).into();
+                }
 
                 let queue = &display.queue;
                 queue.write_buffer(
@@ -275,92 +258,7 @@ impl AppState for MandelbrotState {
         output.present();
     }
 
-    fn event<'a>(&mut self, display: &mut Display, event: WindowEvent) {
-        match event {
-            WindowEvent::CursorMoved { position, .. } => {
-                self.cursor_location = PhysicalPosition {
-                    x: position.x as f32,
-                    y: position.y as f32,
-                };
-
-                self.interaction_state = match self.interaction_state.clone() {
-                    InteractionState::PanningIdle {
-                        pressed_down_at: _,
-                        pre_tap: _,
-                    }
-                    | InteractionState::Zooming
-                    | InteractionState::Panning => {
-                        let dx = (self.cursor_location.x - self.last_cursor_location.x)
-                            / display.size.width as f32;
-                        let dy = (self.cursor_location.y - self.last_cursor_location.y)
-                            / display.size.height as f32;
-                        self.uniforms.translation[0] -= dx * self.uniforms.scale;
-                        self.uniforms.translation[1] += dy * self.uniforms.scale;
-
-                        InteractionState::Panning
-                    }
-                    state => state,
-                };
-
-                self.last_cursor_location = self.cursor_location;
-            }
-            WindowEvent::MouseInput {
-                state: elem_state,
-                button,
-                ..
-            } => {
-                self.interaction_state =
-                    if elem_state == ElementState::Pressed && button == MouseButton::Left {
-                        self.last_cursor_location = self.cursor_location;
-                        match self.interaction_state.clone() {
-                            InteractionState::Idle {
-                                last_pressed_down_at,
-                                pre_tap,
-                            } => {
-                                if pre_tap && now_secs() - last_pressed_down_at > 1.0f32 {
-                                    InteractionState::ZoomingOut
-                                } else {
-                                    InteractionState::PanningIdle {
-                                        pressed_down_at: now_secs(),
-                                        pre_tap: false,
-                                    }
-                                }
-                            }
-                            state => state,
-                        }
-                    } else if elem_state == ElementState::Released && button == MouseButton::Left {
-                        match self.interaction_state.clone() {
-                            InteractionState::PanningIdle {
-                                pre_tap: false,
-                                pressed_down_at,
-                            } => {
-                                if now_secs() - pressed_down_at < 0.3f32 {
-                                    InteractionState::Idle {
-                                        last_pressed_down_at: pressed_down_at,
-                                        pre_tap: true,
-                                    }
-                                } else {
-                                    InteractionState::Idle {
-                                        last_pressed_down_at: pressed_down_at,
-                                        pre_tap: false,
-                                    }
-                                }
-                            }
-                            InteractionState::Zooming
-                            | InteractionState::ZoomingOut
-                            | InteractionState::Panning => InteractionState::Idle {
-                                last_pressed_down_at: now_secs(),
-                                pre_tap: false,
-                            },
-                            state => state,
-                        }
-                    } else {
-                        self.interaction_state.clone()
-                    };
-
-                log::info!("{}", self.interaction_state.clone());
-            }
-            _ => {}
-        }
+    fn event<'a>(&mut self, _display: &mut Display, event: WindowEvent) {
+        self.gestures.event(&event);
     }
 }
@@ -1,147 +1,539 @@
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::{Arc, Mutex};
 use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
 use winit::event::{DeviceEvent, WindowEvent};
-use winit::event_loop::ActiveEventLoop;
+use winit::event_loop::{ActiveEventLoop, EventLoopProxy};
 use winit::window::{Window, WindowId};
 
-#[cfg(target_arch = "wasm32")]
-extern crate console_error_panic_hook;
-
-use super::display::Display;
+use super::display::{Command, Display, DisplayConfig, WindowOptions};
+use super::error::Error;
+use super::input::{ActionMap, Actions, PointerEvent};
+use super::logging::{self, Logging};
+use super::pacing::{LoopMode, Pacer};
+use super::time::{FixedTimestep, FrameClock};
 
 pub(crate) struct App<'a, State: AppState> {
-    pub display: Option<Arc<Mutex<Display<'a>>>>,
-    #[cfg(not(target_arch = "wasm32"))]
-    pub initial_size: PhysicalSize<u32>,
-    #[cfg(target_arch = "wasm32")]
-    pub canvas: wgpu::web_sys::HtmlCanvasElement,
+    pub displays: HashMap<WindowId, Arc<Mutex<Display<'a>>>>,
+    pub main_window: Option<WindowId>,
+    pub window_options: WindowOptions,
     pub state: Option<Arc<Mutex<State>>>,
+    pub error: Option<Error>,
+    pub phase: Phase,
+    pub proxy: EventLoopProxy<AppEvent>,
+    pub pacer: Pacer,
+    pub clock: FrameClock,
+}
+
+pub(crate) enum Phase {
+    /// Waiting for the first `resumed`, which creates the main window.
+    Starting,
+    /// The main display is being created. Window events are queued until the state exists.
+    Initializing(Vec<(WindowId, WindowEvent)>),
+    Running,
+    /// The device was lost and the main display is being recreated. Window events are queued
+    /// until the state recreated its resources.
+    Recovering(Vec<(WindowId, WindowEvent)>),
+}
+
+pub(crate) enum AppEvent {
+    DisplayCreated(Result<Display<'static>, Error>),
+    DisplayRecreated(Result<Display<'static>, Error>),
+}
+
+/// Polls a display future with `wasm_bindgen_futures::spawn_local` on the web, where blocking
+/// would hang the browser, and blocks on it elsewhere.
+fn spawn(future: impl Future<Output = ()> + 'static) {
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(future);
+    #[cfg(not(target_arch = "wasm32"))]
+    pollster::block_on(future);
 }
 
+/// The per-window callbacks (`draw`, `event`, `window_opened`) receive the display of the window
+/// they are called for, so apps with several windows can tell them apart by
+/// [`Display::window_id`] or [`Display::is_main`].
 pub trait AppState {
     fn new(display: &mut Display) -> Self;
-    fn create_display(window: Window) -> Display<'static> {
-        Display::from_window(window)
+    fn display_config() -> DisplayConfig {
+        DisplayConfig::default()
+    }
+    /// How the global logger is set up when the app starts.
+    fn logging() -> Logging {
+        Logging::default()
+    }
+    /// The loop mode the app starts in, see [`Display::set_loop_mode`] to change it later.
+    fn loop_mode() -> LoopMode {
+        LoopMode::default()
+    }
+    /// Bindings the [`Actions`] of every display start with, e.g. from [`ActionMap::load`].
+    fn actions() -> ActionMap {
+        ActionMap::default()
+    }
+    /// Tick rate and catch-up limit of [`AppState::update`].
+    fn timestep() -> FixedTimestep {
+        FixedTimestep::default()
+    }
+    /// Creates the display of the main window. The future is polled with
+    /// `wasm_bindgen_futures::spawn_local` on the web and blocked on elsewhere, and
+    /// [`AppState::new`] is called once it resolves.
+    fn create_display(
+        window: Window,
+    ) -> impl Future<Output = Result<Display<'static>, Error>> + 'static {
+        Display::new_async(window, Self::display_config())
     }
 
-    fn draw(&mut self, display: &mut Display);
+    /// Called once with the error that stopped the app, right before the event loop exits.
+    fn fatal_error(error: &Error) {
+        log::error!("{}", error);
+    }
+
+    /// Advances the simulation by [`FrameClock::fixed_dt`] seconds, which is the same every
+    /// time. Runs as often as [`AppState::timestep`] asks for, right before the main window
+    /// draws, and always receives the main window's display.
+    fn update(&mut self, display: &mut Display, clock: &mut FrameClock) {
+        let (_, _) = (display, clock); // suppress warning
+    }
+    /// [`FrameClock::alpha`] tells how far the frame is between two updates, for interpolating
+    /// between simulation states.
+    fn draw(&mut self, display: &mut Display, clock: &mut FrameClock);
 
     fn event(&mut self, display: &mut Display, event: WindowEvent) {
         let (_, _) = (event, display); // suppress warning
     }
+    /// Called right after [`AppState::event`] for the mouse, touch and pen events of a window,
+    /// translated into [`PointerEvent`]s.
+    fn pointer_event(&mut self, display: &mut Display, event: PointerEvent) {
+        let (_, _) = (event, display); // suppress warning
+    }
+    /// Receives the display of the main window, as device events don't belong to a window.
     fn device_event(&mut self, display: &mut Display, event: DeviceEvent) {
         let (_, _) = (event, display); // suppress warning
     }
-}
 
-fn init_logger() {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let base_level = log::LevelFilter::Info;
-        let wgpu_level = log::LevelFilter::Error;
-
-        fern::Dispatch::new()
-            .level(base_level)
-            .level_for("wgpu_core", wgpu_level)
-            .level_for("wgpu_hal", wgpu_level)
-            .level_for("naga", wgpu_level)
-            .chain(fern::Output::call(console_log::log))
-            .apply()
-            .unwrap();
-        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    /// Called with the display of a window opened through [`Display::open_window`].
+    fn window_opened(&mut self, display: &mut Display) {
+        let _ = display; // suppress warning
     }
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        let base_level = log::LevelFilter::Info;
-        let wgpu_level = log::LevelFilter::Error;
-
-        // parse_default_env will read the RUST_LOG environment variable and apply it on top
-        // of these default filters
-        env_logger::builder()
-            .filter_level(base_level)
-            .filter_module("wgpu_core", wgpu_level)
-            .filter_module("wgpu_hal", wgpu_level)
-            .filter_module("naga", wgpu_level)
-            .parse_default_env()
-            .init();
+    /// Called when the user tries to close a window. Returning `false` keeps it open, e.g. to
+    /// ask about unsaved changes first; it can be closed later through
+    /// [`Display::close_window`] or [`Display::exit`]. Closing the main window quits the app.
+    fn close_requested(&mut self, display: &mut Display) -> bool {
+        let _ = display; // suppress warning
+        true
+    }
+    /// Called once when the app quits, with the display of the main window, before the state
+    /// and the displays are dropped.
+    fn on_exit(&mut self, display: &mut Display) {
+        let _ = display; // suppress warning
+    }
+
+    /// Called after a window other than the main one was closed and its display dropped.
+    fn window_closed(&mut self, id: WindowId) {
+        let _ = id; // suppress warning
+    }
+
+    /// Called for every display after the device was lost and the displays were recreated with a
+    /// new one. Pipelines, buffers and other resources created from the old device have to be
+    /// created again.
+    fn recreate_resources(&mut self, display: &mut Display) {
+        let _ = display; // suppress warning
+    }
+
+    /// Called for every display when the app is suspended, right before its surface is dropped.
+    /// The state and the device outlive the suspension.
+    fn suspended(&mut self, display: &mut Display) {
+        let _ = display; // suppress warning
+    }
+    /// Called for every display once the app is resumed and its surface was recreated. Not
+    /// called for the first resume, which creates the state through [`AppState::new`] instead.
+    fn resumed(&mut self, display: &mut Display) {
+        let _ = display; // suppress warning
     }
 }
 
 impl<'a, State: AppState> App<'a, State> {
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn from_window_size(width: u32, height: u32) -> Self {
-        log::debug!("Setting window size");
+    pub fn new(window_options: WindowOptions, proxy: EventLoopProxy<AppEvent>) -> Self {
         App {
-            initial_size: winit::dpi::PhysicalSize::new(width, height),
-            display: None,
+            window_options,
+            displays: HashMap::new(),
+            main_window: None,
             state: None,
+            error: None,
+            phase: Phase::Starting,
+            proxy,
+            pacer: Pacer::new(State::loop_mode()),
+            clock: FrameClock::new(State::timestep()),
         }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn from_canvas(canvas: wgpu::web_sys::HtmlCanvasElement) -> Self {
-        log::debug!("Setting canvas size");
-        App {
-            canvas,
-            display: None,
-            state: None,
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: Error) {
+        State::fatal_error(&error);
+        self.error = Some(error);
+        event_loop.exit();
+    }
+
+    fn main_display(&self) -> Option<&Arc<Mutex<Display<'a>>>> {
+        self.main_window.and_then(|id| self.displays.get(&id))
+    }
+
+    fn run_commands(&mut self, event_loop: &ActiveEventLoop, commands: Vec<Command>) {
+        for command in commands {
+            match command {
+                Command::OpenWindow(options) => self.open_window(event_loop, *options),
+                Command::CloseWindow(id) => self.close_window(event_loop, id),
+                Command::Exit => event_loop.exit(),
+                Command::SetLoopMode(mode) => {
+                    self.pacer.set_mode(mode);
+                    for display in self.displays.values() {
+                        display.lock().unwrap().request_redraw();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Creates the state once the main display is ready and replays the window events that
+    /// arrived in the meantime.
+    fn display_created(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        display: Result<Display<'static>, Error>,
+    ) {
+        let Phase::Initializing(events) = std::mem::replace(&mut self.phase, Phase::Running) else {
+            return;
+        };
+        let mut display = match display {
+            Ok(display) => display,
+            Err(err) => return self.fail(event_loop, err),
+        };
+
+        log::debug!("{}", display.capabilities());
+        display.actions = Actions::new(State::actions());
+        let new_state = State::new(&mut display);
+        let commands = display.take_commands();
+        display.request_redraw();
+        if let Some(id) = display.window_id() {
+            self.main_window = Some(id);
+            self.displays.insert(id, Arc::new(Mutex::new(display)));
+        }
+        self.state = Some(Arc::new(Mutex::new(new_state)));
+        self.run_commands(event_loop, commands);
+
+        log::debug!("Replaying {} events from initialization", events.len());
+        for (id, event) in events {
+            self.window_event(event_loop, id, event);
+        }
+    }
+
+    /// Recreates the surfaces dropped by `suspended`, keeping the state and the device.
+    fn resume(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        log::debug!("Resumed");
+        self.clock.reset();
+
+        let mut state = state.lock().unwrap();
+        let mut commands = Vec::new();
+        let mut error = None;
+        for display in self.displays.values() {
+            let mut display = display.lock().unwrap();
+            match display.resume() {
+                Ok(()) => {
+                    state.resumed(&mut display);
+                    display.request_redraw();
+                }
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+            commands.append(&mut display.take_commands());
+        }
+
+        drop(state);
+        match error {
+            Some(err) => self.fail(event_loop, err),
+            None => self.run_commands(event_loop, commands),
+        }
+    }
+
+    fn device_lost(&self) -> bool {
+        self.main_display()
+            .is_some_and(|display| display.lock().unwrap().is_device_lost())
+    }
+
+    /// Requests a new device for the main display. The other displays share it, so they are
+    /// recreated in `display_recreated` once it is ready.
+    fn recover(&mut self) {
+        let Some(main_display) = self.main_display() else {
+            return;
+        };
+        if matches!(self.phase, Phase::Recovering(_)) {
+            return;
+        }
+        log::warn!("Recreating displays after the device was lost");
+
+        let display = main_display.lock().unwrap().recreate_async();
+        self.phase = Phase::Recovering(Vec::new());
+        let proxy = self.proxy.clone();
+        spawn(async move {
+            // Only fails if the event loop is gone, in which case there is nothing left to do
+            let _ = proxy.send_event(AppEvent::DisplayRecreated(display.await));
+        });
+    }
+
+    /// Swaps in the recreated main display, recreates the others with its device, lets the
+    /// state rebuild its resources and replays the window events that arrived in the meantime.
+    fn display_recreated(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        display: Result<Display<'static>, Error>,
+    ) {
+        let Phase::Recovering(events) = std::mem::replace(&mut self.phase, Phase::Running) else {
+            return;
+        };
+        let display = match display {
+            Ok(display) => display,
+            Err(err) => return self.fail(event_loop, err),
+        };
+        let (Some(main_display), Some(state)) = (self.main_display(), self.state.as_ref()) else {
+            return;
+        };
+
+        let mut main_display = main_display.lock().unwrap();
+        let mut state = state.lock().unwrap();
+        main_display.replace_with(display);
+        state.recreate_resources(&mut main_display);
+        main_display.request_redraw();
+        let mut commands = main_display.take_commands();
+
+        for (id, display) in &self.displays {
+            if Some(*id) == self.main_window {
+                continue;
+            }
+            let mut display = display.lock().unwrap();
+            match display.recreate_shared(&main_display) {
+                Ok(()) => state.recreate_resources(&mut display),
+                Err(err) => log::error!("Couldn't recreate display of {:?}: {}", id, err),
+            }
+            display.request_redraw();
+            commands.append(&mut display.take_commands());
+        }
+
+        drop((main_display, state));
+        self.run_commands(event_loop, commands);
+
+        log::debug!("Replaying {} events from recovery", events.len());
+        for (id, event) in events {
+            self.window_event(event_loop, id, event);
+        }
+    }
+
+    fn open_window(&mut self, event_loop: &ActiveEventLoop, options: WindowOptions) {
+        let (Some(main_display), Some(state)) = (self.main_display(), self.state.as_ref()) else {
+            return;
+        };
+
+        let display = Display::create_window(event_loop, &options)
+            .and_then(|window| main_display.lock().unwrap().share_with_window(window));
+        let mut display = match display {
+            Ok(display) => display,
+            Err(err) => {
+                log::error!("Couldn't open window: {}", err);
+                return;
+            }
+        };
+
+        display.actions = Actions::new(State::actions());
+        state.lock().unwrap().window_opened(&mut display);
+        let commands = display.take_commands();
+        if let Some(id) = display.window_id() {
+            log::debug!("Window opened: {:?}", id);
+            self.displays.insert(id, Arc::new(Mutex::new(display)));
+        }
+        self.run_commands(event_loop, commands);
+    }
+
+    fn close_window(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
+        if Some(id) == self.main_window {
+            event_loop.exit();
+            return;
+        }
+
+        if self.displays.remove(&id).is_some() {
+            log::debug!("Window closed: {:?}", id);
+            if let Some(state) = &self.state {
+                state.lock().unwrap().window_closed(id);
+            }
         }
     }
 }
 
-impl<'a, State: AppState> ApplicationHandler for App<'a, State> {
+impl<'a, State: AppState> ApplicationHandler<AppEvent> for App<'a, State> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        init_logger();
+        logging::init(State::logging);
 
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            self.display = Some(Arc::new(Mutex::new(State::create_display(
-                Display::create_window_from_size(event_loop, self.initial_size),
-            ))));
+        match self.phase {
+            Phase::Starting => (),
+            // Recovery recreates every surface once the new device is ready
+            Phase::Initializing(_) | Phase::Recovering(_) => return,
+            Phase::Running => return self.resume(event_loop),
         }
-        #[cfg(target_arch = "wasm32")]
-        {
-            self.display = Some(Arc::new(Mutex::new(Display::from_window(
-                Display::create_window_from_canvas(event_loop, self.canvas.clone()),
-            ))));
+
+        let window = match Display::create_window(event_loop, &self.window_options) {
+            Ok(window) => window,
+            Err(err) => return self.fail(event_loop, err),
+        };
+        self.phase = Phase::Initializing(Vec::new());
+
+        let display = State::create_display(window);
+        let proxy = self.proxy.clone();
+        spawn(async move {
+            // Only fails if the event loop is gone, in which case there is nothing left to do
+            let _ = proxy.send_event(AppEvent::DisplayCreated(display.await));
+        });
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: AppEvent) {
+        match event {
+            AppEvent::DisplayCreated(display) => self.display_created(event_loop, display),
+            AppEvent::DisplayRecreated(display) => self.display_recreated(event_loop, display),
         }
+    }
 
-        let new_state = State::new(&mut self.display.clone().unwrap().lock().unwrap());
-        self.state = Some(Arc::new(Mutex::new(new_state)));
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        log::debug!("Suspended");
+
+        let mut state = state.lock().unwrap();
+        for display in self.displays.values() {
+            let mut display = display.lock().unwrap();
+            state.suspended(&mut display);
+            display.suspend();
+        }
     }
 
-    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
-        let mut display = self.display.as_ref().clone().unwrap().lock().unwrap();
-        let mut state = self.state.as_ref().clone().unwrap().lock().unwrap();
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+        if let Phase::Initializing(events) | Phase::Recovering(events) = &mut self.phase {
+            events.push((id, event));
+            return;
+        }
 
-        state.event(&mut display, event.clone());
+        let (commands, fatal_error) = {
+            let (Some(display), Some(state)) = (self.displays.get(&id), self.state.as_ref()) else {
+                return;
+            };
+            let mut display = display.lock().unwrap();
+            let mut state = state.lock().unwrap();
 
-        match event {
-            WindowEvent::CloseRequested => {
-                event_loop.exit();
+            let pointer_event =
+                PointerEvent::from_window_event(&event, &display.input, display.scale_factor);
+            display.input.window_event(&event);
+            display.actions.window_event(&event);
+            state.event(&mut display, event.clone());
+            if let Some(pointer_event) = pointer_event {
+                state.pointer_event(&mut display, pointer_event);
             }
-            WindowEvent::Resized(physical_size) => {
-                log::debug!("Window resized: {:?}", physical_size);
-                display.resize(physical_size);
+            if self.pacer.redraw_after_event(&event) {
+                display.request_redraw();
             }
-            WindowEvent::RedrawRequested => {
-                state.draw(&mut display);
-                display.window.as_ref().request_redraw();
-            }
-            _ => (),
+
+            match event {
+                WindowEvent::CloseRequested if state.close_requested(&mut display) => {
+                    display.close_window(id);
+                }
+                WindowEvent::Resized(physical_size) => {
+                    log::debug!("Window resized: {:?}", physical_size);
+                    display.resize(physical_size);
+                }
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    log::debug!("Scale factor changed: {}", scale_factor);
+                    display.set_scale_factor(scale_factor);
+                }
+                // Resuming requests the next redraw, so that a suspended app doesn't spin
+                WindowEvent::RedrawRequested if !display.is_suspended() => {
+                    let Display { actions, input, .. } = &mut *display;
+                    actions.update(input);
+                    // The simulation advances once per frame, with the main window's display
+                    if self.main_window == Some(id) {
+                        let steps = self.clock.tick(web_time::Instant::now());
+                        for _ in 0..steps {
+                            display.actions.begin_update();
+                            state.update(&mut display, &mut self.clock);
+                            display.actions.end_update();
+                        }
+                    }
+                    state.draw(&mut display, &mut self.clock);
+                    display.input.end_frame();
+                    if self.pacer.redraw_after_draw() {
+                        display.request_redraw();
+                    }
+                }
+                _ => (),
+            };
+
+            (display.take_commands(), display.take_fatal_error())
         };
+
+        self.run_commands(event_loop, commands);
+        if let Some(err) = fatal_error {
+            self.fail(event_loop, err);
+        } else if self.device_lost() {
+            self.recover();
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let (control_flow, redraw) = self.pacer.schedule(web_time::Instant::now());
+        if redraw {
+            for display in self.displays.values() {
+                let display = display.lock().unwrap();
+                if !display.is_suspended() {
+                    display.request_redraw();
+                }
+            }
+        }
+        event_loop.set_control_flow(control_flow);
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let (Some(display), Some(state)) = (self.main_display(), &self.state) {
+            state.lock().unwrap().on_exit(&mut display.lock().unwrap());
+        }
+
+        // The state's resources were created from the displays' device, so it goes first
+        self.state = None;
+        self.displays.clear();
     }
 
     fn device_event(
         &mut self,
-        _event_loop: &ActiveEventLoop,
+        event_loop: &ActiveEventLoop,
         _device_id: winit::event::DeviceId,
         event: winit::event::DeviceEvent,
     ) {
-        let mut display = self.display.as_ref().clone().unwrap().lock().unwrap();
-        let mut state = self.state.as_ref().clone().unwrap().lock().unwrap();
+        let (commands, fatal_error) = {
+            let (Some(display), Some(state)) = (self.main_display(), self.state.as_ref()) else {
+                return;
+            };
+            let mut display = display.lock().unwrap();
+            let mut state = state.lock().unwrap();
+
+            display.input.device_event(&event);
+            state.device_event(&mut display, event.clone());
 
-        state.device_event(&mut display, event.clone());
+            (display.take_commands(), display.take_fatal_error())
+        };
+
+        self.run_commands(event_loop, commands);
+        if let Some(err) = fatal_error {
+            self.fail(event_loop, err);
+        } else if self.device_lost() {
+            self.recover();
+        }
     }
 }
@@ -0,0 +1,137 @@
+/// A texture owned by [`Display`](super::Display) that is recreated whenever the display is
+/// resized, such as the depth buffer or the multisampled color target.
+pub struct RenderAttachment {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    format_views: Vec<(wgpu::TextureFormat, wgpu::TextureView)>,
+}
+
+impl RenderAttachment {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        view_formats: &[wgpu::TextureFormat],
+        sample_count: u32,
+        label: &str,
+    ) -> Self {
+        let mut usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        if sample_count == 1 {
+            usage |= wgpu::TextureUsages::TEXTURE_BINDING;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: config.width.max(1),
+                height: config.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let format_views = view_formats
+            .iter()
+            .map(|&format| {
+                let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                    format: Some(format),
+                    ..Default::default()
+                });
+                (format, view)
+            })
+            .collect();
+
+        Self {
+            texture,
+            view,
+            format_views,
+        }
+    }
+
+    /// A view in `format`, or `None` if it is neither the texture's format nor one of its view
+    /// formats. Like [`Frame::view_as`](super::Frame::view_as).
+    pub fn view_as(&self, format: wgpu::TextureFormat) -> Option<&wgpu::TextureView> {
+        if format == self.format() {
+            return Some(&self.view);
+        }
+
+        self.format_views
+            .iter()
+            .find(|(view_format, _)| *view_format == format)
+            .map(|(_, view)| view)
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.texture.sample_count()
+    }
+}
+
+/// Creates the multisampled color target and the depth buffer a display needs for its current
+/// size, in that order. The color target can be viewed in the surface's view formats, so it
+/// resolves into frames viewed in them.
+pub(crate) fn create_attachments(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    depth_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
+) -> (Option<RenderAttachment>, Option<RenderAttachment>) {
+    let msaa = (sample_count > 1).then(|| {
+        RenderAttachment::new(
+            device,
+            config,
+            config.format,
+            &config.view_formats,
+            sample_count,
+            "MSAA Texture",
+        )
+    });
+    let depth = depth_format.map(|format| {
+        RenderAttachment::new(device, config, format, &[], sample_count, "Depth Texture")
+    });
+
+    (msaa, depth)
+}
+
+/// Returns `requested` if the device can multisample both formats with it, or 1 otherwise.
+///
+/// Like wgpu's validation, only trusts the adapter's format features if the device has
+/// `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES` or is a downlevel one, and the features every
+/// device guarantees otherwise.
+pub(crate) fn supported_sample_count(
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    color_format: wgpu::TextureFormat,
+    depth_format: Option<wgpu::TextureFormat>,
+    requested: u32,
+) -> u32 {
+    let adapter_specific = device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        || !adapter.get_downlevel_capabilities().is_webgpu_compliant();
+    let supported = std::iter::once(color_format)
+        .chain(depth_format)
+        .all(|format| {
+            let features = if adapter_specific {
+                adapter.get_texture_format_features(format)
+            } else {
+                format.guaranteed_format_features(device.features())
+            };
+            features.flags.sample_count_supported(requested)
+        });
+
+    if supported {
+        requested
+    } else {
+        log::warn!("Sample count {} isn't supported, disabling MSAA", requested);
+        1
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+use super::Display;
+
+/// What the adapter behind a [`Display`] supports, for picking render paths at startup.
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    pub adapter_info: wgpu::AdapterInfo,
+    pub backend: wgpu::Backend,
+    /// Features the adapter supports, not only the ones the device was created with.
+    pub features: wgpu::Features,
+    /// Limits the adapter supports, not only the ones the device was created with.
+    pub limits: wgpu::Limits,
+    pub downlevel: wgpu::DownlevelCapabilities,
+    /// Empty for headless displays.
+    pub surface_formats: Vec<wgpu::TextureFormat>,
+    /// Empty for headless displays.
+    pub present_modes: Vec<wgpu::PresentMode>,
+}
+
+impl Capabilities {
+    /// Whether compute shaders are available, which WebGL2 and some downlevel backends lack.
+    pub fn supports_compute(&self) -> bool {
+        self.downlevel
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = &self.adapter_info;
+        writeln!(
+            f,
+            "Adapter: {} ({:?}, {:?})",
+            info.name, info.device_type, self.backend
+        )?;
+        writeln!(f, "Driver: {} {}", info.driver, info.driver_info)?;
+        writeln!(f, "Features: {:?}", self.features)?;
+        writeln!(f, "Downlevel flags: {:?}", self.downlevel.flags)?;
+        writeln!(f, "Shader model: {:?}", self.downlevel.shader_model)?;
+        writeln!(
+            f,
+            "Max texture size: {}, max storage buffers per stage: {}",
+            self.limits.max_texture_dimension_2d, self.limits.max_storage_buffers_per_shader_stage
+        )?;
+        writeln!(f, "Surface formats: {:?}", self.surface_formats)?;
+        write!(f, "Present modes: {:?}", self.present_modes)
+    }
+}
+
+impl<'a> Display<'a> {
+    /// Collects what the adapter and surface support. Implements `std::fmt::Display` for
+    /// logging a diagnostic report.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            adapter_info: self.adapter.get_info(),
+            backend: self.backend(),
+            features: self.adapter.features(),
+            limits: self.adapter.limits(),
+            downlevel: self.adapter.get_downlevel_capabilities(),
+            surface_formats: self.surface_capabilities.formats.clone(),
+            present_modes: self.surface_capabilities.present_modes.clone(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::SurfaceFormat;
+
+/// Settings used by [`Display`](super::Display) when it creates the instance, adapter, device
+/// and surface configuration.
+///
+/// Every field falls back to what sursface picked before it was configurable, so
+/// `DisplayConfig::default()` behaves exactly like [`Display::from_window`](super::Display::from_window).
+#[derive(Clone, Debug)]
+pub struct DisplayConfig {
+    /// In the browser, `BROWSER_WEBGPU | GL` with the `webgpu` feature, where WebGL2 is used if
+    /// WebGPU turns out to be unavailable, and `GL` without it.
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    pub force_fallback_adapter: bool,
+    pub required_features: wgpu::Features,
+    /// If `None`, the WebGL2 downlevel limits are requested on WebGL2 and the WebGPU defaults
+    /// on every other backend.
+    pub required_limits: Option<wgpu::Limits>,
+    pub surface_format: SurfaceFormat,
+    /// Extra formats frames can be viewed as through [`Frame::view_as`](super::Frame::view_as),
+    /// e.g. the sRGB variant of a linear surface format. Formats that differ from the surface
+    /// format in more than being sRGB are ignored.
+    pub view_formats: Vec<wgpu::TextureFormat>,
+    /// Falls back as described in [`Display::set_present_mode`](super::Display::set_present_mode)
+    /// if unsupported. If `None`, the first supported present mode is picked.
+    pub present_mode: Option<wgpu::PresentMode>,
+    /// Used if the surface supports it, otherwise the first supported alpha mode is picked.
+    pub alpha_mode: Option<wgpu::CompositeAlphaMode>,
+    pub desired_maximum_frame_latency: u32,
+    /// Format of the depth/stencil attachment owned by the display, if it should have one.
+    pub depth_format: Option<wgpu::TextureFormat>,
+    /// Number of samples per pixel. Values above 1 make the display render into a multisampled
+    /// target that is resolved to the frame. Falls back to 1 if the adapter doesn't support it.
+    pub sample_count: u32,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            backends: wgpu::Backends::PRIMARY,
+            #[cfg(all(target_arch = "wasm32", feature = "webgpu"))]
+            backends: wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL,
+            #[cfg(all(target_arch = "wasm32", not(feature = "webgpu")))]
+            backends: wgpu::Backends::GL,
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            required_features: wgpu::Features::empty(),
+            required_limits: None,
+            surface_format: SurfaceFormat::default(),
+            view_formats: Vec::new(),
+            present_mode: None,
+            alpha_mode: None,
+            desired_maximum_frame_latency: 2,
+            depth_format: None,
+            sample_count: 1,
+        }
+    }
+}
+
+impl DisplayConfig {
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn with_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn with_features(mut self, required_features: wgpu::Features) -> Self {
+        self.required_features = required_features;
+        self
+    }
+
+    pub fn with_limits(mut self, required_limits: wgpu::Limits) -> Self {
+        self.required_limits = Some(required_limits);
+        self
+    }
+
+    /// Accepts a [`SurfaceFormat`] policy or an explicit `wgpu::TextureFormat`.
+    pub fn with_surface_format(mut self, format: impl Into<SurfaceFormat>) -> Self {
+        self.surface_format = format.into();
+        self
+    }
+
+    pub fn with_view_formats(mut self, view_formats: Vec<wgpu::TextureFormat>) -> Self {
+        self.view_formats = view_formats;
+        self
+    }
+
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = Some(present_mode);
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: wgpu::CompositeAlphaMode) -> Self {
+        self.alpha_mode = Some(alpha_mode);
+        self
+    }
+
+    pub fn with_frame_latency(mut self, desired_maximum_frame_latency: u32) -> Self {
+        self.desired_maximum_frame_latency = desired_maximum_frame_latency;
+        self
+    }
+
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+}
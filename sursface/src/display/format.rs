@@ -0,0 +1,87 @@
+use wgpu::TextureFormat;
+
+/// How [`Display`](super::Display) picks the surface format among the ones the surface supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SurfaceFormat {
+    /// The first sRGB format, so shaders write linear colors that get encoded on store.
+    #[default]
+    Srgb,
+    /// An 8-bit non-sRGB format, so values are stored as written. Falls back to sRGB.
+    Linear,
+    /// `Rgba16Float`, which platforms that support it present with an extended linear color
+    /// space, or `Rgb10a2Unorm`. Falls back to sRGB.
+    Hdr,
+    /// The given format, falling back to sRGB if unsupported.
+    Explicit(TextureFormat),
+}
+
+impl From<TextureFormat> for SurfaceFormat {
+    fn from(format: TextureFormat) -> Self {
+        SurfaceFormat::Explicit(format)
+    }
+}
+
+impl SurfaceFormat {
+    pub(crate) fn select(self, supported: &[TextureFormat]) -> TextureFormat {
+        let preferred = match self {
+            SurfaceFormat::Srgb => None,
+            SurfaceFormat::Linear => [TextureFormat::Bgra8Unorm, TextureFormat::Rgba8Unorm]
+                .into_iter()
+                .find(|f| supported.contains(f))
+                .or_else(|| supported.iter().copied().find(|f| !f.is_srgb())),
+            SurfaceFormat::Hdr => [TextureFormat::Rgba16Float, TextureFormat::Rgb10a2Unorm]
+                .into_iter()
+                .find(|f| supported.contains(f)),
+            SurfaceFormat::Explicit(format) => Some(format).filter(|f| supported.contains(f)),
+        };
+        if preferred.is_none() && self != SurfaceFormat::Srgb {
+            log::warn!("No surface format matches {:?}, falling back to sRGB", self);
+        }
+
+        preferred
+            .or_else(|| supported.iter().copied().find(|f| f.is_srgb()))
+            .unwrap_or(supported[0])
+    }
+}
+
+/// Keeps the view formats that only differ from `format` in being sRGB or not, which are the
+/// only ones surfaces can be viewed as. Drops all of them if the device can't create views in
+/// other formats, like on GL, so that [`Frame::view_as`](super::Frame::view_as) returns `None`.
+pub(crate) fn compatible_view_formats(
+    format: TextureFormat,
+    view_formats: &[TextureFormat],
+    downlevel: wgpu::DownlevelFlags,
+    surface: bool,
+) -> Vec<TextureFormat> {
+    let view_formats: Vec<TextureFormat> = view_formats
+        .iter()
+        .copied()
+        .filter(|&view_format| {
+            let compatible = view_format != format
+                && view_format.remove_srgb_suffix() == format.remove_srgb_suffix();
+            if !compatible && view_format != format {
+                log::warn!(
+                    "Ignoring view format {:?}, which is incompatible with {:?}",
+                    view_format,
+                    format
+                );
+            }
+            compatible
+        })
+        .collect();
+
+    // The MSAA target is created with the view formats of the surface as well
+    let mut required = wgpu::DownlevelFlags::VIEW_FORMATS;
+    if surface {
+        required |= wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS;
+    }
+    if !view_formats.is_empty() && !downlevel.contains(required) {
+        log::warn!(
+            "Ignoring view formats {:?}, as the device can't view textures in other formats",
+            view_formats
+        );
+        return Vec::new();
+    }
+
+    view_formats
+}
@@ -0,0 +1,73 @@
+use wgpu::{SurfaceTexture, Texture, TextureFormat, TextureView};
+
+/// A texture acquired from [`Display::get_framebuffer`](super::Display::get_framebuffer) to
+/// draw a single frame into.
+pub struct Frame {
+    pub view: TextureView,
+    pub format: TextureFormat,
+    format_views: Vec<(TextureFormat, TextureView)>,
+    surface_texture: Option<SurfaceTexture>,
+}
+
+impl Frame {
+    pub(crate) fn from_surface_texture(
+        surface_texture: SurfaceTexture,
+        view_formats: &[TextureFormat],
+    ) -> Self {
+        Self {
+            view: surface_texture
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+            format: surface_texture.texture.format(),
+            format_views: create_format_views(&surface_texture.texture, view_formats),
+            surface_texture: Some(surface_texture),
+        }
+    }
+
+    pub(crate) fn from_texture(texture: &Texture, view_formats: &[TextureFormat]) -> Self {
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            format: texture.format(),
+            format_views: create_format_views(texture, view_formats),
+            surface_texture: None,
+        }
+    }
+
+    /// A view of the frame in one of the configured
+    /// [`view_formats`](super::DisplayConfig::view_formats), or `None` if it wasn't configured.
+    /// The surface format itself always resolves to [`Frame::view`].
+    pub fn view_as(&self, format: TextureFormat) -> Option<&TextureView> {
+        if format == self.format {
+            return Some(&self.view);
+        }
+
+        self.format_views
+            .iter()
+            .find(|(view_format, _)| *view_format == format)
+            .map(|(_, view)| view)
+    }
+
+    /// Presents the frame to the window. Does nothing for headless displays, whose offscreen
+    /// texture keeps the frame until the next draw.
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+fn create_format_views(
+    texture: &Texture,
+    view_formats: &[TextureFormat],
+) -> Vec<(TextureFormat, TextureView)> {
+    view_formats
+        .iter()
+        .map(|&format| {
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                format: Some(format),
+                ..Default::default()
+            });
+            (format, view)
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use winit::window::Window;
+
+use super::DisplayConfig;
+use crate::error::Error;
+
+/// Everything a display needs from wgpu before it can configure its render target.
+pub(crate) struct Gpu {
+    pub instance: wgpu::Instance,
+    /// The surface of the window the GPU was requested for, if any.
+    pub surface: Option<wgpu::Surface<'static>>,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+}
+
+impl Gpu {
+    /// Requests an adapter, device and queue, creating a surface for `window` if given.
+    ///
+    /// In the browser, `BROWSER_WEBGPU` is tried on its own first, and the remaining backends
+    /// (WebGL2) are used if the browser doesn't expose WebGPU or has no WebGPU adapter. The
+    /// surface is only created once the backend is settled, as a canvas can't switch contexts.
+    pub(crate) async fn request(
+        window: Option<Arc<Window>>,
+        config: &DisplayConfig,
+    ) -> Result<Self, Error> {
+        let backends = config.backends;
+        if cfg!(target_arch = "wasm32") && backends.contains(wgpu::Backends::BROWSER_WEBGPU) {
+            let webgpu =
+                Self::request_with_backends(window.clone(), wgpu::Backends::BROWSER_WEBGPU, config)
+                    .await;
+            match webgpu {
+                Ok(gpu) => return Ok(gpu),
+                Err(err) if backends != wgpu::Backends::BROWSER_WEBGPU => {
+                    log::warn!("Couldn't use WebGPU, falling back to WebGL2: {}", err)
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Self::request_with_backends(
+            window,
+            backends.difference(wgpu::Backends::BROWSER_WEBGPU),
+            config,
+        )
+        .await
+    }
+
+    async fn request_with_backends(
+        window: Option<Arc<Window>>,
+        backends: wgpu::Backends,
+        config: &DisplayConfig,
+    ) -> Result<Self, Error> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
+
+        // WebGPU adapters don't depend on the surface, so it's only created once one is found
+        let webgpu = backends == wgpu::Backends::BROWSER_WEBGPU;
+        let mut surface = match &window {
+            Some(window) if !webgpu => Some(instance.create_surface(Arc::clone(window))?),
+            _ => None,
+        };
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: config.power_preference,
+                compatible_surface: surface.as_ref(),
+                force_fallback_adapter: config.force_fallback_adapter,
+            })
+            .await
+            .ok_or(Error::NoAdapter)?;
+
+        let required_limits = config
+            .required_limits
+            .clone()
+            .unwrap_or_else(|| default_limits(adapter.get_info().backend));
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    required_features: config.required_features,
+                    required_limits,
+                },
+                None,
+            )
+            .await?;
+
+        if let (Some(window), true) = (window, webgpu) {
+            surface = Some(instance.create_surface(window)?);
+        }
+
+        Ok(Self {
+            instance,
+            surface,
+            adapter,
+            device,
+            queue,
+        })
+    }
+}
+
+/// Limits requested when the config doesn't set any: the WebGL2 downlevel limits for WebGL2 in
+/// the browser, and the WebGPU defaults everywhere else.
+fn default_limits(backend: wgpu::Backend) -> wgpu::Limits {
+    if cfg!(target_arch = "wasm32") && backend == wgpu::Backend::Gl {
+        wgpu::Limits::downlevel_webgl2_defaults()
+    } else {
+        wgpu::Limits::default()
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use winit::dpi::PhysicalSize;
+
+use super::gpu::Gpu;
+use super::{attachment, format, recovery, Display, DisplayConfig};
+use crate::error::Error;
+use crate::input::{Actions, Input};
+
+impl<'a> Display<'a> {
+    /// Creates a display without a window, drawing into an offscreen texture of the given size
+    /// and format instead. Besides the primary backends it tries GL, through which machines
+    /// without a GPU reach software adapters such as llvmpipe.
+    pub fn headless(width: u32, height: u32, format: wgpu::TextureFormat) -> Result<Self, Error> {
+        let mut config = DisplayConfig::default();
+        config.backends |= wgpu::Backends::GL;
+        Self::headless_with_config(width, height, format, &config)
+    }
+
+    /// Like [`Display::headless`], with the given config. llvmpipe is only reached through GL, so
+    /// the config's backends have to include `Backends::GL` to run without a GPU.
+    pub fn headless_with_config(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        config: &DisplayConfig,
+    ) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::ZeroSize(PhysicalSize::new(width, height)));
+        }
+
+        let Gpu {
+            instance,
+            adapter,
+            device,
+            queue,
+            ..
+        } = pollster::block_on(Gpu::request(None, config))?;
+
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            desired_maximum_frame_latency: config.desired_maximum_frame_latency,
+            view_formats: format::compatible_view_formats(
+                format,
+                &config.view_formats,
+                adapter.get_downlevel_capabilities().flags,
+                false,
+            ),
+        };
+        let device_lost = recovery::watch_device(&device);
+        let offscreen = create_offscreen_texture(&device, &surface_config);
+        let sample_count = attachment::supported_sample_count(
+            &adapter,
+            &device,
+            surface_config.format,
+            config.depth_format,
+            config.sample_count,
+        );
+        let (msaa, depth) = attachment::create_attachments(
+            &device,
+            &surface_config,
+            config.depth_format,
+            sample_count,
+        );
+
+        Ok(Self {
+            window: None,
+            size: PhysicalSize::new(width, height),
+            scale_factor: 1.0,
+            surface: None,
+            offscreen: Some(offscreen),
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            config: surface_config,
+            surface_capabilities: wgpu::SurfaceCapabilities::default(),
+            depth,
+            msaa,
+            input: Input::default(),
+            actions: Actions::default(),
+            instance: Arc::new(instance),
+            adapter: Arc::new(adapter),
+            display_config: config.clone(),
+            main: true,
+            pending_present_mode: None,
+            device_lost,
+            commands: Vec::new(),
+            fatal_error: None,
+        })
+    }
+
+    /// Copies the offscreen texture of a headless display back to the CPU, blocking until the
+    /// GPU is done with it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_frame(&self) -> Result<image::RgbaImage, Error> {
+        let texture = self.offscreen.as_ref().ok_or(Error::NoRenderTarget)?;
+
+        let swizzle = match self.config.format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => return Err(Error::UnsupportedFormat(format)),
+        };
+
+        let (width, height) = (self.config.width, self.config.height);
+        let unpadded_bytes_per_row = 4 * width;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| Error::Readback(wgpu::BufferAsyncError))??;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        if swizzle {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Ok(image::RgbaImage::from_raw(width, height, pixels)
+            .expect("Readback buffer matches the frame size"))
+    }
+}
+
+pub(crate) fn create_offscreen_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &config.view_formats,
+    })
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use super::{format, present, Display};
+use crate::error::Error;
+
+impl<'a> Display<'a> {
+    /// Whether the surface was dropped because the app was suspended. Frames are skipped until
+    /// it is resumed.
+    pub fn is_suspended(&self) -> bool {
+        self.window.is_some() && self.surface.is_none()
+    }
+
+    /// Drops the surface, keeping the device, queue and everything created from them.
+    pub(crate) fn suspend(&mut self) {
+        self.surface = None;
+    }
+
+    /// Creates the surface again after [`Display::suspend`], keeping the configuration where the
+    /// new surface still supports it.
+    pub(crate) fn resume(&mut self) -> Result<(), Error> {
+        let Some(window) = self.window.clone() else {
+            return Ok(());
+        };
+        if self.surface.is_some() {
+            return Ok(());
+        }
+
+        let surface = self.instance.create_surface(Arc::clone(&window))?;
+        self.surface_capabilities = surface.get_capabilities(&self.adapter);
+        let caps = &self.surface_capabilities;
+        if !caps.formats.contains(&self.config.format) {
+            let surface_format = self.display_config.surface_format.select(&caps.formats);
+            log::warn!(
+                "Surface format {:?} is no longer supported, switching to {:?}",
+                self.config.format,
+                surface_format
+            );
+            self.config.format = surface_format;
+            self.config.view_formats = format::compatible_view_formats(
+                surface_format,
+                &self.display_config.view_formats,
+                self.adapter.get_downlevel_capabilities().flags,
+                true,
+            );
+        }
+        self.config.present_mode =
+            present::resolve_present_mode(self.config.present_mode, &caps.present_modes);
+        if !caps.alpha_modes.contains(&self.config.alpha_mode) {
+            self.config.alpha_mode = caps.alpha_modes[0];
+        }
+        self.surface = Some(surface);
+
+        // Also recreates the attachments, as the window may have been resized while suspended
+        self.resize(window.inner_size());
+
+        Ok(())
+    }
+}
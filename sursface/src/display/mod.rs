@@ -1,115 +1,225 @@
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
-use winit::dpi::PhysicalSize;
 use winit::event_loop::ActiveEventLoop;
-use winit::window::{Window, WindowAttributes};
+use winit::window::{Window, WindowId};
 
-#[cfg(target_arch = "wasm32")]
-use winit::platform::web::WindowAttributesExtWebSys;
+pub mod attachment;
+pub mod capabilities;
+pub mod config;
+pub mod format;
+pub mod frame;
+mod gpu;
+pub mod headless;
+mod lifecycle;
+mod present;
+mod recovery;
+mod scale;
+pub mod window;
+
+pub use attachment::RenderAttachment;
+pub use capabilities::Capabilities;
+pub use config::DisplayConfig;
+pub use format::SurfaceFormat;
+pub use frame::Frame;
+pub use window::{Fullscreen, WindowOptions};
+
+use crate::error::Error;
+use crate::input::{Actions, Input};
+use crate::pacing::LoopMode;
+use gpu::Gpu;
 
 pub struct Display<'a> {
-    pub window: Arc<Window>,
+    /// `None` for headless displays.
+    pub window: Option<Arc<Window>>,
     pub size: winit::dpi::PhysicalSize<u32>,
-    pub surface: wgpu::Surface<'a>,
-    pub device: wgpu::Device,
-    pub queue: wgpu::Queue,
+    /// Ratio of physical to logical pixels of the window, 1 for headless displays.
+    pub scale_factor: f64,
+    /// `None` for headless displays, which draw into `offscreen` instead, and while suspended.
+    pub surface: Option<wgpu::Surface<'a>>,
+    pub offscreen: Option<wgpu::Texture>,
+    /// Shared with every other display of the app.
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+    /// Shared with every other display of the app. See also [`Display::capabilities`].
+    pub adapter: Arc<wgpu::Adapter>,
     pub config: wgpu::SurfaceConfiguration,
+    /// Formats, present modes and alpha modes the surface supports, empty for headless displays.
+    pub surface_capabilities: wgpu::SurfaceCapabilities,
+    pub depth: Option<RenderAttachment>,
+    /// Multisampled color target that `std::clear` renders into and resolves to the frame.
+    pub msaa: Option<RenderAttachment>,
+    /// Keyboard and mouse state of the window, updated before every callback that receives it.
+    pub input: Input,
+    /// Actions and axes bound through [`AppState::actions`](crate::app::AppState::actions).
+    pub actions: Actions,
+    pub(crate) instance: Arc<wgpu::Instance>,
+    pub(crate) display_config: DisplayConfig,
+    pub(crate) main: bool,
+    pub(crate) pending_present_mode: Option<wgpu::PresentMode>,
+    /// Shared by every display using the same device.
+    pub(crate) device_lost: Arc<AtomicBool>,
+    pub(crate) commands: Vec<Command>,
+    pub(crate) fatal_error: Option<Error>,
+}
+
+/// Requests queued by a [`Display`] for the app to carry out once the current callback returns.
+pub(crate) enum Command {
+    OpenWindow(Box<WindowOptions>),
+    CloseWindow(WindowId),
+    SetLoopMode(LoopMode),
+    Exit,
 }
 
 impl<'a> Display<'a> {
-    pub(crate) fn create_window_from_size(
+    pub(crate) fn create_window(
         event_loop: &ActiveEventLoop,
-        window_size: PhysicalSize<u32>,
-    ) -> Window {
-        let window = event_loop
-            .create_window(WindowAttributes::default().with_inner_size(window_size))
-            .expect("Couldn't create window");
+        options: &WindowOptions,
+    ) -> Result<Window, Error> {
+        Ok(event_loop.create_window(options.attributes(event_loop))?)
+    }
 
-        window
+    pub fn from_window(window: Window) -> Result<Self, Error> {
+        Self::from_window_with_config(window, &DisplayConfig::default())
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub(crate) fn create_window_from_canvas(
-        event_loop: &ActiveEventLoop,
-        canvas: wgpu::web_sys::HtmlCanvasElement,
-    ) -> Window {
-        let window = event_loop
-            .create_window(WindowAttributes::default().with_canvas(Some(canvas)))
-            .expect("Couldn't create window");
-
-        window
-    }
-
-    pub fn from_window(window: Window) -> Self {
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            #[cfg(not(target_arch = "wasm32"))]
-            backends: wgpu::Backends::PRIMARY,
-            #[cfg(target_arch = "wasm32")]
-            backends: wgpu::Backends::GL,
-            ..Default::default()
-        });
-
-        let window = Arc::new(window);
-        let surface = instance
-            .create_surface(Arc::clone(&window))
-            .expect("Couldn't create surface");
-
-        let (adapter, device, queue) = pollster::block_on(async {
-            let adapter = instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::default(),
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
-                })
-                .await
-                .expect("Couldn't get adapter");
-
-            let (device, queue) = adapter
-                .request_device(
-                    &wgpu::DeviceDescriptor {
-                        label: None,
-                        required_features: wgpu::Features::empty(),
-                        required_limits: if cfg!(target_arch = "wasm32") {
-                            wgpu::Limits::downlevel_webgl2_defaults()
-                        } else {
-                            wgpu::Limits::default()
-                        },
-                    },
-                    None,
-                )
-                .await
-                .unwrap();
-
-            (adapter, device, queue)
-        });
+    pub fn from_window_with_config(window: Window, config: &DisplayConfig) -> Result<Self, Error> {
+        pollster::block_on(Self::from_shared_window(Arc::new(window), config))
+    }
+
+    /// Like [`Display::from_window_with_config`], but awaits the adapter and device instead of
+    /// blocking on them, which isn't possible on the browser main thread. Takes the config by
+    /// value so the future can be spawned.
+    pub async fn new_async(window: Window, config: DisplayConfig) -> Result<Self, Error> {
+        Self::from_shared_window(Arc::new(window), &config).await
+    }
+
+    async fn from_shared_window(
+        window: Arc<Window>,
+        config: &DisplayConfig,
+    ) -> Result<Self, Error> {
+        let gpu = Gpu::request(Some(Arc::clone(&window)), config).await?;
+        let surface = gpu.surface.ok_or(Error::NoRenderTarget)?;
+
+        let mut display = Self::from_surface(
+            window,
+            surface,
+            Arc::new(gpu.instance),
+            Arc::new(gpu.adapter),
+            Arc::new(gpu.device),
+            Arc::new(gpu.queue),
+            config,
+        );
+        display.device_lost = recovery::watch_device(&display.device);
+
+        Ok(display)
+    }
+
+    /// Creates a display for another window, sharing this display's device and queue so that
+    /// resources can be used in both.
+    pub fn share_with_window(&self, window: Window) -> Result<Display<'static>, Error> {
+        self.share_with_shared_window(Arc::new(window))
+    }
+
+    fn share_with_shared_window(&self, window: Arc<Window>) -> Result<Display<'static>, Error> {
+        let surface = self.instance.create_surface(Arc::clone(&window))?;
+        if !self.adapter.is_surface_supported(&surface) {
+            return Err(Error::UnsupportedSurface);
+        }
 
+        let mut display = Display::from_surface(
+            window,
+            surface,
+            Arc::clone(&self.instance),
+            Arc::clone(&self.adapter),
+            Arc::clone(&self.device),
+            Arc::clone(&self.queue),
+            &self.display_config,
+        );
+        display.main = false;
+        display.device_lost = Arc::clone(&self.device_lost);
+
+        Ok(display)
+    }
+
+    fn from_surface(
+        window: Arc<Window>,
+        surface: wgpu::Surface<'a>,
+        instance: Arc<wgpu::Instance>,
+        adapter: Arc<wgpu::Adapter>,
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        config: &DisplayConfig,
+    ) -> Self {
         let size = window.inner_size();
+        let scale_factor = window.scale_factor();
 
         let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.is_srgb())
-            .unwrap_or(surface_caps.formats[0]);
-        let config = wgpu::SurfaceConfiguration {
+        let surface_format = config.surface_format.select(&surface_caps.formats);
+        let present_mode = match config.present_mode {
+            Some(present_mode) => {
+                present::resolve_present_mode(present_mode, &surface_caps.present_modes)
+            }
+            None => surface_caps.present_modes[0],
+        };
+        let alpha_mode = config
+            .alpha_mode
+            .filter(|m| surface_caps.alpha_modes.contains(m))
+            .unwrap_or(surface_caps.alpha_modes[0]);
+        let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            desired_maximum_frame_latency: 2,
-            view_formats: vec![],
+            present_mode,
+            alpha_mode,
+            desired_maximum_frame_latency: config.desired_maximum_frame_latency,
+            view_formats: format::compatible_view_formats(
+                surface_format,
+                &config.view_formats,
+                adapter.get_downlevel_capabilities().flags,
+                true,
+            ),
         };
+        if size.width > 0 && size.height > 0 {
+            surface.configure(&device, &surface_config);
+        }
+        let sample_count = attachment::supported_sample_count(
+            &adapter,
+            &device,
+            surface_config.format,
+            config.depth_format,
+            config.sample_count,
+        );
+        let (msaa, depth) = attachment::create_attachments(
+            &device,
+            &surface_config,
+            config.depth_format,
+            sample_count,
+        );
 
         Self {
-            window,
+            window: Some(window),
             size,
-            surface,
+            scale_factor,
+            surface: Some(surface),
+            offscreen: None,
             device,
             queue,
-            config,
+            config: surface_config,
+            surface_capabilities: surface_caps,
+            depth,
+            msaa,
+            input: Input::default(),
+            actions: Actions::default(),
+            instance,
+            adapter,
+            display_config: config.clone(),
+            main: true,
+            pending_present_mode: None,
+            device_lost: Arc::default(),
+            commands: Vec::new(),
+            fatal_error: None,
         }
     }
 
@@ -118,11 +228,184 @@ impl<'a> Display<'a> {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            if let Some(surface) = &self.surface {
+                surface.configure(&self.device, &self.config);
+            }
+            if self.offscreen.is_some() {
+                self.offscreen = Some(headless::create_offscreen_texture(
+                    &self.device,
+                    &self.config,
+                ));
+            }
+            (self.msaa, self.depth) = attachment::create_attachments(
+                &self.device,
+                &self.config,
+                self.depth.as_ref().map(RenderAttachment::format),
+                self.sample_count(),
+            );
         }
     }
 
-    pub fn window(&self) -> &Window {
-        &self.window
+    /// Acquires the texture to draw the next frame into: the next surface texture for windowed
+    /// displays, or the offscreen texture for headless ones.
+    ///
+    /// Returns `None` if the frame should be skipped. Fatal errors are not returned, but handed
+    /// to [`AppState::fatal_error`](crate::app::AppState::fatal_error) once the current callback
+    /// returns; use [`Display::try_get_framebuffer`] to handle them yourself.
+    pub fn get_framebuffer(&mut self) -> Option<Frame> {
+        match self.try_get_framebuffer() {
+            Ok(frame) => frame,
+            Err(err) => {
+                self.fatal_error = Some(err);
+                None
+            }
+        }
+    }
+
+    /// Like [`Display::get_framebuffer`], but returns fatal errors instead of reporting them.
+    ///
+    /// A lost or outdated surface is reconfigured and acquired again, and a timed-out
+    /// acquisition or a suspended display skips the frame by returning `Ok(None)`.
+    pub fn try_get_framebuffer(&mut self) -> Result<Option<Frame>, Error> {
+        self.apply_pending_present_mode();
+        let Some(surface) = &self.surface else {
+            return match &self.offscreen {
+                Some(texture) => Ok(Some(Frame::from_texture(
+                    texture,
+                    &self.config.view_formats,
+                ))),
+                None if self.is_suspended() => Ok(None),
+                None => Err(Error::NoRenderTarget),
+            };
+        };
+
+        if self.config.width == 0 || self.config.height == 0 {
+            return Ok(None);
+        }
+
+        let mut reconfigured = false;
+        loop {
+            match surface.get_current_texture() {
+                Ok(surface_texture) => {
+                    return Ok(Some(Frame::from_surface_texture(
+                        surface_texture,
+                        &self.config.view_formats,
+                    )))
+                }
+                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) if !reconfigured => {
+                    log::debug!("Surface lost or outdated, reconfiguring");
+                    surface.configure(&self.device, &self.config);
+                    reconfigured = true;
+                }
+                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                    log::debug!("Surface still unusable after reconfiguring, skipping frame");
+                    return Ok(None);
+                }
+                Err(wgpu::SurfaceError::Timeout) => {
+                    log::debug!("Surface acquisition timed out, skipping frame");
+                    return Ok(None);
+                }
+                Err(err @ wgpu::SurfaceError::OutOfMemory) => return Err(err.into()),
+            }
+        }
+    }
+
+    pub(crate) fn take_fatal_error(&mut self) -> Option<Error> {
+        self.fatal_error.take()
+    }
+
+    /// Number of samples per pixel of the display's render targets.
+    pub fn sample_count(&self) -> u32 {
+        self.msaa.as_ref().map_or(1, RenderAttachment::sample_count)
+    }
+
+    /// The backend the adapter was picked from, e.g. to tell WebGPU from its WebGL2 fallback
+    /// in the browser.
+    pub fn backend(&self) -> wgpu::Backend {
+        self.adapter.get_info().backend
+    }
+
+    /// Limits of the device, which may be lower than the adapter's.
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    pub fn window(&self) -> Option<&Window> {
+        self.window.as_deref()
+    }
+
+    /// Id of the window this display belongs to, `None` for headless displays.
+    pub fn window_id(&self) -> Option<WindowId> {
+        self.window.as_ref().map(|window| window.id())
+    }
+
+    /// Whether this is the window the app was started with, as opposed to one opened with
+    /// [`Display::open_window`].
+    pub fn is_main(&self) -> bool {
+        self.main
+    }
+
+    /// Opens another window sharing this display's device and queue. Once it exists,
+    /// [`AppState::window_opened`](crate::app::AppState::window_opened) is called with its display.
+    pub fn open_window(&mut self, options: WindowOptions) {
+        self.commands.push(Command::OpenWindow(Box::new(options)));
+    }
+
+    /// Closes a window opened with [`Display::open_window`]. Closing the main window exits the app.
+    pub fn close_window(&mut self, id: WindowId) {
+        self.commands.push(Command::CloseWindow(id));
+    }
+
+    pub fn set_title(&self, title: &str) {
+        if let Some(window) = self.window() {
+            window.set_title(title);
+        }
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        if let Some(window) = self.window() {
+            window.set_fullscreen(window::resolve_fullscreen(
+                fullscreen,
+                window.current_monitor(),
+            ));
+        }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.window()
+            .is_some_and(|window| window.fullscreen().is_some())
+    }
+
+    /// Switches between windowed and borderless fullscreen.
+    pub fn toggle_fullscreen(&self) {
+        let fullscreen = (!self.is_fullscreen()).then_some(Fullscreen::Borderless);
+        self.set_fullscreen(fullscreen);
+    }
+
+    /// Quits the app once the current callback returns, after calling
+    /// [`AppState::on_exit`](crate::app::AppState::on_exit).
+    pub fn exit(&mut self) {
+        self.commands.push(Command::Exit);
+    }
+
+    /// Asks for this window to be drawn again, which is how apps in
+    /// [`LoopMode::Reactive`] redraw without input. Does nothing for headless displays.
+    pub fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+
+    /// Switches how often the app redraws its windows once the current callback returns.
+    pub fn set_loop_mode(&mut self, mode: LoopMode) {
+        self.commands.push(Command::SetLoopMode(mode));
+    }
+
+    pub(crate) fn take_commands(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.commands)
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
     }
 }
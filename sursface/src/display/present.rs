@@ -0,0 +1,79 @@
+use wgpu::PresentMode;
+
+use super::Display;
+
+impl<'a> Display<'a> {
+    /// Present modes the surface supports, empty for headless displays.
+    pub fn supported_present_modes(&self) -> &[PresentMode] {
+        &self.surface_capabilities.present_modes
+    }
+
+    /// The present mode the surface uses, or will use from the next frame on if it was just
+    /// changed.
+    pub fn present_mode(&self) -> PresentMode {
+        self.pending_present_mode
+            .unwrap_or(self.config.present_mode)
+    }
+
+    /// Switches the present mode starting with the next frame, and returns the mode that will
+    /// actually be used. Unsupported modes fall back in this order:
+    ///
+    /// - `Mailbox` → `Immediate` → `Fifo`
+    /// - `Immediate` → `Mailbox` → `Fifo`
+    /// - `FifoRelaxed` → `Fifo`
+    ///
+    /// `AutoVsync` and `AutoNoVsync` are resolved by wgpu, to `FifoRelaxed` → `Fifo` and
+    /// `Immediate` → `Mailbox` → `Fifo` respectively. `Fifo` is supported everywhere.
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) -> PresentMode {
+        let present_mode = resolve_present_mode(present_mode, self.supported_present_modes());
+        if present_mode != self.config.present_mode {
+            self.pending_present_mode = Some(present_mode);
+        } else {
+            self.pending_present_mode = None;
+        }
+        present_mode
+    }
+
+    /// Shorthand for switching between `AutoVsync` and `AutoNoVsync`.
+    pub fn set_vsync(&mut self, vsync: bool) -> PresentMode {
+        self.set_present_mode(if vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        })
+    }
+
+    /// Reconfigures the surface with a present mode requested since the last frame.
+    pub(crate) fn apply_pending_present_mode(&mut self) {
+        let Some(present_mode) = self.pending_present_mode.take() else {
+            return;
+        };
+
+        log::debug!("Switching present mode to {:?}", present_mode);
+        self.config.present_mode = present_mode;
+        if let Some(surface) = &self.surface {
+            if self.config.width > 0 && self.config.height > 0 {
+                surface.configure(&self.device, &self.config);
+            }
+        }
+    }
+}
+
+pub(crate) fn resolve_present_mode(
+    requested: PresentMode,
+    supported: &[PresentMode],
+) -> PresentMode {
+    let fallbacks: &[PresentMode] = match requested {
+        PresentMode::AutoVsync | PresentMode::AutoNoVsync => return requested,
+        PresentMode::Mailbox => &[PresentMode::Mailbox, PresentMode::Immediate],
+        PresentMode::Immediate => &[PresentMode::Immediate, PresentMode::Mailbox],
+        PresentMode::FifoRelaxed => &[PresentMode::FifoRelaxed],
+        PresentMode::Fifo => &[],
+    };
+
+    fallbacks
+        .iter()
+        .copied()
+        .find(|mode| supported.contains(mode))
+        .unwrap_or(PresentMode::Fifo)
+}
@@ -0,0 +1,99 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::Display;
+use crate::error::Error;
+
+impl<'a> Display<'a> {
+    /// Whether the device was lost, e.g. because the driver was reset. The app recreates the
+    /// displays and calls [`AppState::recreate_resources`](crate::app::AppState::recreate_resources)
+    /// once the current callback returns.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
+
+    /// Requests a new adapter, device and queue and recreates the surface or offscreen texture
+    /// and attachments with them. Everything created from the old device has to be recreated
+    /// as well.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn recreate(&mut self) -> Result<(), Error> {
+        let display = match &self.window {
+            Some(_) => pollster::block_on(self.recreate_async())?,
+            None => Display::headless_with_config(
+                self.config.width,
+                self.config.height,
+                self.config.format,
+                &self.display_config,
+            )?,
+        };
+        self.replace_with(display);
+
+        Ok(())
+    }
+
+    /// Drops the surface and returns a future that creates a display for the same window with
+    /// a new device, to be swapped in through `replace_with`. Blocking on it would hang the
+    /// browser, so the app spawns it like the initial display.
+    pub(crate) fn recreate_async(
+        &mut self,
+    ) -> impl Future<Output = Result<Display<'static>, Error>> + 'static {
+        // Some platforms refuse to create a second surface for a window
+        self.surface = None;
+
+        let window = self.window.clone();
+        let config = self.display_config.clone();
+        async move {
+            let window = window.ok_or(Error::NoRenderTarget)?;
+            Display::from_shared_window(window, &config).await
+        }
+    }
+
+    /// Recreates the surface of a secondary window with the device of an already recreated main
+    /// display.
+    pub(crate) fn recreate_shared(&mut self, main: &Display) -> Result<(), Error> {
+        let Some(window) = self.window.clone() else {
+            #[cfg(not(target_arch = "wasm32"))]
+            return self.recreate();
+            #[cfg(target_arch = "wasm32")]
+            return Err(Error::NoRenderTarget);
+        };
+
+        self.surface = None;
+        let display = main.share_with_shared_window(window)?;
+        self.replace_with(display);
+
+        Ok(())
+    }
+
+    pub(crate) fn replace_with(&mut self, mut display: Display<'a>) {
+        display.set_present_mode(self.present_mode());
+        display.main = self.main;
+        display.input = std::mem::take(&mut self.input);
+        display.actions = std::mem::take(&mut self.actions);
+        display.commands = std::mem::take(&mut self.commands);
+        display.fatal_error = self.fatal_error.take();
+        *self = display;
+    }
+}
+
+/// Registers the device-lost and uncaptured-error callbacks of a newly created device and
+/// returns the flag set once it is lost.
+pub(crate) fn watch_device(device: &wgpu::Device) -> Arc<AtomicBool> {
+    let device_lost = Arc::new(AtomicBool::new(false));
+
+    let flag = Arc::clone(&device_lost);
+    device.set_device_lost_callback(move |reason, message| match reason {
+        // The device was dropped by us or the callback replaced, nothing was lost
+        wgpu::DeviceLostReason::Dropped | wgpu::DeviceLostReason::ReplacedCallback => (),
+        _ => {
+            log::error!("Device lost ({:?}): {}", reason, message);
+            flag.store(true, Ordering::Release);
+        }
+    });
+    device.on_uncaptured_error(Box::new(|err| {
+        log::error!("Uncaptured wgpu error: {}", err);
+    }));
+
+    device_lost
+}
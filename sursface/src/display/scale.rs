@@ -0,0 +1,46 @@
+use cgmath::Vector2;
+use winit::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Pixel};
+
+use super::Display;
+
+impl<'a> Display<'a> {
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        if let Some(window) = self.window() {
+            let size = window.inner_size();
+            self.resize(size);
+        }
+    }
+
+    pub fn physical_size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn logical_size(&self) -> LogicalSize<f64> {
+        self.size.to_logical(self.scale_factor)
+    }
+
+    pub fn to_logical<P: Pixel>(&self, position: PhysicalPosition<P>) -> LogicalPosition<f64> {
+        position.to_logical(self.scale_factor)
+    }
+
+    pub fn to_physical<P: Pixel>(&self, position: LogicalPosition<P>) -> PhysicalPosition<f64> {
+        position.to_physical(self.scale_factor)
+    }
+
+    /// Maps a position in physical pixels to `[0, 1]`, with the origin in the top left corner.
+    pub fn to_uv<P: Pixel>(&self, position: PhysicalPosition<P>) -> Vector2<f32> {
+        let position: PhysicalPosition<f64> = position.cast();
+        Vector2::new(
+            (position.x / self.size.width.max(1) as f64) as f32,
+            (position.y / self.size.height.max(1) as f64) as f32,
+        )
+    }
+
+    /// Maps a position in physical pixels to normalized device coordinates, from `-1` to `1`
+    /// with y pointing up.
+    pub fn to_ndc<P: Pixel>(&self, position: PhysicalPosition<P>) -> Vector2<f32> {
+        let uv = self.to_uv(position);
+        Vector2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0)
+    }
+}
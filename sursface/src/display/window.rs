@@ -0,0 +1,179 @@
+use winit::dpi::{Position, Size};
+use winit::event_loop::ActiveEventLoop;
+use winit::monitor::MonitorHandle;
+use winit::window::{Icon, WindowAttributes};
+
+#[cfg(target_arch = "wasm32")]
+use winit::platform::web::WindowAttributesExtWebSys;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fullscreen {
+    /// Covers the current monitor with a borderless window.
+    Borderless,
+    /// Switches the current monitor to its largest video mode, falling back to borderless if it
+    /// has none.
+    Exclusive,
+}
+
+/// Attributes of a window created by sursface, either at startup or through
+/// [`Display::open_window`](super::Display::open_window).
+#[derive(Clone, Debug)]
+pub struct WindowOptions {
+    pub title: String,
+    pub icon: Option<image::RgbaImage>,
+    pub inner_size: Option<Size>,
+    pub min_inner_size: Option<Size>,
+    pub max_inner_size: Option<Size>,
+    pub position: Option<Position>,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub maximized: bool,
+    pub fullscreen: Option<Fullscreen>,
+    pub transparent: bool,
+    #[cfg(target_arch = "wasm32")]
+    pub canvas: Option<wgpu::web_sys::HtmlCanvasElement>,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self {
+            title: "sursface".to_string(),
+            icon: None,
+            inner_size: None,
+            min_inner_size: None,
+            max_inner_size: None,
+            position: None,
+            resizable: true,
+            decorations: true,
+            maximized: false,
+            fullscreen: None,
+            transparent: false,
+            #[cfg(target_arch = "wasm32")]
+            canvas: None,
+        }
+    }
+}
+
+impl WindowOptions {
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the window icon, e.g. from `image::load_from_memory(bytes)?.to_rgba8()`.
+    pub fn with_icon(mut self, icon: image::RgbaImage) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn with_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.inner_size = Some(size.into());
+        self
+    }
+
+    pub fn with_min_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.min_inner_size = Some(size.into());
+        self
+    }
+
+    pub fn with_max_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.max_inner_size = Some(size.into());
+        self
+    }
+
+    pub fn with_position(mut self, position: impl Into<Position>) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn with_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = maximized;
+        self
+    }
+
+    pub fn with_fullscreen(mut self, fullscreen: Option<Fullscreen>) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn with_canvas(mut self, canvas: wgpu::web_sys::HtmlCanvasElement) -> Self {
+        self.canvas = Some(canvas);
+        self
+    }
+
+    pub(crate) fn attributes(&self, event_loop: &ActiveEventLoop) -> WindowAttributes {
+        let mut attributes = WindowAttributes::default()
+            .with_title(self.title.clone())
+            .with_window_icon(self.icon.as_ref().and_then(create_icon))
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_maximized(self.maximized)
+            .with_transparent(self.transparent)
+            .with_fullscreen(resolve_fullscreen(
+                self.fullscreen,
+                event_loop.primary_monitor(),
+            ));
+
+        if let Some(size) = self.inner_size {
+            attributes = attributes.with_inner_size(size);
+        }
+        if let Some(size) = self.min_inner_size {
+            attributes = attributes.with_min_inner_size(size);
+        }
+        if let Some(size) = self.max_inner_size {
+            attributes = attributes.with_max_inner_size(size);
+        }
+        if let Some(position) = self.position {
+            attributes = attributes.with_position(position);
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            attributes = attributes.with_canvas(self.canvas.clone());
+        }
+
+        attributes
+    }
+}
+
+pub(crate) fn resolve_fullscreen(
+    fullscreen: Option<Fullscreen>,
+    monitor: Option<MonitorHandle>,
+) -> Option<winit::window::Fullscreen> {
+    match fullscreen? {
+        Fullscreen::Borderless => Some(winit::window::Fullscreen::Borderless(monitor)),
+        Fullscreen::Exclusive => {
+            let video_mode = monitor.as_ref().and_then(|monitor| {
+                monitor.video_modes().max_by_key(|mode| {
+                    let size = mode.size();
+                    (size.width * size.height, mode.refresh_rate_millihertz())
+                })
+            });
+            match video_mode {
+                Some(video_mode) => Some(winit::window::Fullscreen::Exclusive(video_mode)),
+                None => Some(winit::window::Fullscreen::Borderless(monitor)),
+            }
+        }
+    }
+}
+
+fn create_icon(image: &image::RgbaImage) -> Option<Icon> {
+    Icon::from_rgba(image.as_raw().clone(), image.width(), image.height())
+        .map_err(|err| log::warn!("Couldn't use window icon: {}", err))
+        .ok()
+}
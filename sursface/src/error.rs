@@ -0,0 +1,121 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    EventLoop(winit::error::EventLoopError),
+    WindowCreation(winit::error::OsError),
+    SurfaceCreation(wgpu::CreateSurfaceError),
+    NoAdapter,
+    /// The adapter shared with the main display can't present to a new window's surface.
+    UnsupportedSurface,
+    RequestDevice(wgpu::RequestDeviceError),
+    SurfaceAcquisition(wgpu::SurfaceError),
+    /// The display has neither a surface nor an offscreen texture to draw into.
+    NoRenderTarget,
+    /// A headless display was requested with a zero width or height.
+    ZeroSize(winit::dpi::PhysicalSize<u32>),
+    UnsupportedFormat(wgpu::TextureFormat),
+    Readback(wgpu::BufferAsyncError),
+    Io(std::io::Error),
+    ParseBindings(ron::error::SpannedError),
+    SaveBindings(ron::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EventLoop(err) => write!(f, "Event loop failed: {}", err),
+            Error::WindowCreation(err) => write!(f, "Couldn't create window: {}", err),
+            Error::SurfaceCreation(err) => write!(f, "Couldn't create surface: {}", err),
+            Error::NoAdapter => write!(f, "Couldn't get a suitable adapter"),
+            Error::UnsupportedSurface => {
+                write!(f, "The shared adapter can't present to the window's surface")
+            }
+            Error::RequestDevice(err) => write!(f, "Couldn't get device: {}", err),
+            Error::SurfaceAcquisition(err) => {
+                write!(f, "Couldn't acquire surface texture: {}", err)
+            }
+            Error::NoRenderTarget => write!(f, "Display has no render target"),
+            Error::ZeroSize(size) => {
+                write!(f, "Can't render into a {}x{} target", size.width, size.height)
+            }
+            Error::UnsupportedFormat(format) => write!(f, "Unsupported format: {:?}", format),
+            Error::Readback(err) => write!(f, "Couldn't read back frame: {}", err),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::ParseBindings(err) => write!(f, "Couldn't parse bindings: {}", err),
+            Error::SaveBindings(err) => write!(f, "Couldn't save bindings: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::EventLoop(err) => Some(err),
+            Error::WindowCreation(err) => Some(err),
+            Error::SurfaceCreation(err) => Some(err),
+            Error::NoAdapter | Error::UnsupportedSurface => None,
+            Error::RequestDevice(err) => Some(err),
+            Error::SurfaceAcquisition(err) => Some(err),
+            Error::NoRenderTarget | Error::ZeroSize(_) | Error::UnsupportedFormat(_) => None,
+            Error::Readback(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::ParseBindings(err) => Some(err),
+            Error::SaveBindings(err) => Some(err),
+        }
+    }
+}
+
+impl From<winit::error::EventLoopError> for Error {
+    fn from(err: winit::error::EventLoopError) -> Self {
+        Error::EventLoop(err)
+    }
+}
+
+impl From<winit::error::OsError> for Error {
+    fn from(err: winit::error::OsError) -> Self {
+        Error::WindowCreation(err)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for Error {
+    fn from(err: wgpu::CreateSurfaceError) -> Self {
+        Error::SurfaceCreation(err)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error {
+    fn from(err: wgpu::RequestDeviceError) -> Self {
+        Error::RequestDevice(err)
+    }
+}
+
+impl From<wgpu::BufferAsyncError> for Error {
+    fn from(err: wgpu::BufferAsyncError) -> Self {
+        Error::Readback(err)
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(err: wgpu::SurfaceError) -> Self {
+        Error::SurfaceAcquisition(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for Error {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Error::ParseBindings(err)
+    }
+}
+
+impl From<ron::Error> for Error {
+    fn from(err: ron::Error) -> Self {
+        Error::SaveBindings(err)
+    }
+}
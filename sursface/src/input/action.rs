@@ -0,0 +1,361 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use cgmath::{Vector2, Zero};
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, WindowEvent};
+use winit::keyboard::KeyCode;
+
+use super::{Gesture, GestureConfig, GestureRecognizer, Input};
+use crate::error::Error;
+
+/// Pixels of touchpad scrolling that count as one line of mouse wheel scrolling.
+const SCROLL_PIXELS_PER_LINE: f64 = 20.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GestureBinding {
+    /// Held for the frame the tap was recognized in.
+    Tap,
+    DoubleTap,
+    /// Held for as long as the long press lasts.
+    LongPress,
+    /// A long press right after a tap.
+    TapAndHold,
+}
+
+/// Something that can be held down, bound to an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gesture(GestureBinding),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+/// Something that produces a value each frame, bound to an axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// -1 while `negative` is held, 1 while `positive` is held and 0 for both or neither.
+    Buttons {
+        negative: Binding,
+        positive: Binding,
+    },
+    /// Lines scrolled this frame, with y pointing up.
+    Scroll(Direction),
+    /// Pixels dragged with one finger or the gesture mouse button this frame.
+    Drag(Direction),
+    /// Raw mouse motion this frame, see [`Input::mouse_motion`].
+    MouseMotion(Direction),
+    /// Natural log of how much two fingers pinched this frame, so that spreading them is
+    /// positive and pinches add up.
+    Pinch,
+    /// Radians two fingers turned clockwise this frame.
+    Rotate,
+}
+
+/// Named actions and axes with their bindings, which can be saved to and loaded from RON.
+///
+/// ```ron
+/// (
+///     actions: {
+///         "zoom_in": [Key(Equal), Gesture(LongPress)],
+///     },
+///     axes: {
+///         "pan_x": [Buttons(negative: Key(ArrowLeft), positive: Key(ArrowRight))],
+///     },
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionMap {
+    pub actions: BTreeMap<String, Vec<Binding>>,
+    pub axes: BTreeMap<String, Vec<AxisBinding>>,
+    /// Thresholds of the gestures that [`Binding::Gesture`] and the gesture axes react to.
+    pub gestures: GestureConfig,
+}
+
+impl ActionMap {
+    /// Adds a binding to an action, creating the action if needed.
+    pub fn with_action(mut self, name: impl Into<String>, binding: Binding) -> Self {
+        self.actions.entry(name.into()).or_default().push(binding);
+        self
+    }
+
+    /// Adds a binding to an axis, creating the axis if needed.
+    pub fn with_axis(mut self, name: impl Into<String>, binding: AxisBinding) -> Self {
+        self.axes.entry(name.into()).or_default().push(binding);
+        self
+    }
+
+    pub fn with_gestures(mut self, gestures: GestureConfig) -> Self {
+        self.gestures = gestures;
+        self
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, Error> {
+        Ok(ron::from_str(ron)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String, Error> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// Reads bindings saved by [`ActionMap::save`]. There is no file system in the browser, use
+    /// [`ActionMap::from_ron`] there.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        Ok(std::fs::write(path, self.to_ron()?)?)
+    }
+}
+
+/// State of the actions and axes of an [`ActionMap`], kept up to date by the app in
+/// [`Display::actions`](crate::display::Display::actions).
+///
+/// Evaluated once per frame from [`Display::input`](crate::display::Display::input) and the
+/// recognized gestures, right before [`AppState::update`](crate::app::AppState::update).
+///
+/// In `draw`, [`Actions::pressed`], [`Actions::released`] and the axis bindings that measure
+/// movement, like scrolling or dragging, cover the frame being drawn. Updates don't run every
+/// frame, so in `update` they cover everything since the previous update instead, and only the
+/// first update of a frame sees them.
+#[derive(Clone, Debug)]
+pub struct Actions {
+    map: ActionMap,
+    gestures: GestureRecognizer,
+    /// Whether the current long press started right after a tap.
+    long_press: Option<bool>,
+    /// Gestures of this frame that are only held for the frame.
+    recognized: HashSet<GestureBinding>,
+    drag: Vector2<f64>,
+    pinch: f64,
+    rotate: f64,
+    held: HashSet<String>,
+    pressed: HashSet<String>,
+    released: HashSet<String>,
+    axes: HashMap<String, f32>,
+    /// Values of the axes without the bindings that measure movement.
+    levels: HashMap<String, f64>,
+    since_update: SinceUpdate,
+    /// Whether the queries answer for `update` rather than `draw`.
+    updating: bool,
+}
+
+/// What happened since the last update, for the next one.
+#[derive(Clone, Debug, Default)]
+struct SinceUpdate {
+    pressed: HashSet<String>,
+    released: HashSet<String>,
+    /// Summed values of the axis bindings that measure movement.
+    deltas: HashMap<String, f64>,
+}
+
+impl Default for Actions {
+    fn default() -> Self {
+        Self::new(ActionMap::default())
+    }
+}
+
+impl Actions {
+    pub fn new(map: ActionMap) -> Self {
+        Self {
+            gestures: GestureRecognizer::new(map.gestures),
+            map,
+            long_press: None,
+            recognized: HashSet::new(),
+            drag: Vector2::zero(),
+            pinch: 0.0,
+            rotate: 0.0,
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+            axes: HashMap::new(),
+            levels: HashMap::new(),
+            since_update: SinceUpdate::default(),
+            updating: false,
+        }
+    }
+
+    pub fn map(&self) -> &ActionMap {
+        &self.map
+    }
+
+    /// Replaces the bindings, e.g. after the user rebound a key. Actions held with the old
+    /// bindings are released on the next frame.
+    pub fn set_map(&mut self, map: ActionMap) {
+        self.gestures.config = map.gestures;
+        self.map = map;
+    }
+
+    /// Whether any binding of the action is held. Keys and buttons that were pressed and
+    /// released within the frame count as held for it.
+    pub fn held(&self, action: &str) -> bool {
+        self.held.contains(action)
+    }
+
+    /// Whether the action started being held this frame, or since the last update when asked
+    /// in `update`, see [`Actions`].
+    pub fn pressed(&self, action: &str) -> bool {
+        if self.updating {
+            self.since_update.pressed.contains(action)
+        } else {
+            self.pressed.contains(action)
+        }
+    }
+
+    /// Whether the action stopped being held, like [`Actions::pressed`].
+    pub fn released(&self, action: &str) -> bool {
+        if self.updating {
+            self.since_update.released.contains(action)
+        } else {
+            self.released.contains(action)
+        }
+    }
+
+    /// Sum of the values of the axis' bindings, 0 for unknown axes. Movement adds up since the
+    /// last update when asked in `update`, see [`Actions`].
+    pub fn axis(&self, axis: &str) -> f32 {
+        if !self.updating {
+            return self.axes.get(axis).copied().unwrap_or(0.0);
+        }
+        let level = self.levels.get(axis).copied().unwrap_or(0.0);
+        let delta = self.since_update.deltas.get(axis).copied().unwrap_or(0.0);
+        (level + delta) as f32
+    }
+
+    pub(crate) fn window_event(&mut self, event: &WindowEvent) {
+        self.gestures.event(event);
+    }
+
+    /// Makes the queries answer for an update until [`Actions::end_update`].
+    pub(crate) fn begin_update(&mut self) {
+        self.updating = true;
+    }
+
+    /// Consumes what happened since the last update, so that later updates of the same frame
+    /// don't see it again.
+    pub(crate) fn end_update(&mut self) {
+        self.updating = false;
+        self.since_update = SinceUpdate::default();
+    }
+
+    /// Evaluates the bindings against the input of the frame that is about to be drawn.
+    pub(crate) fn update(&mut self, input: &Input) {
+        self.recognized.clear();
+        self.drag = Vector2::zero();
+        self.pinch = 0.0;
+        self.rotate = 0.0;
+
+        for gesture in self.gestures.poll() {
+            match gesture {
+                Gesture::Tap { .. } => {
+                    self.recognized.insert(GestureBinding::Tap);
+                }
+                Gesture::DoubleTap { .. } => {
+                    self.recognized.insert(GestureBinding::DoubleTap);
+                }
+                Gesture::LongPress { after_tap, .. } => {
+                    self.long_press = Some(after_tap);
+                    self.recognized.insert(long_press_binding(after_tap));
+                }
+                Gesture::LongPressEnd { .. } => self.long_press = None,
+                Gesture::Drag { delta, .. } => self.drag += delta,
+                Gesture::Pinch { scale, .. } => self.pinch += scale.ln(),
+                Gesture::Rotate { angle, .. } => self.rotate += angle.0,
+                Gesture::DragEnd { .. } | Gesture::Fling { .. } => (),
+            }
+        }
+
+        let held: HashSet<String> = self
+            .map
+            .actions
+            .iter()
+            .filter(|(_, bindings)| bindings.iter().any(|binding| self.is_held(binding, input)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        self.pressed = held.difference(&self.held).cloned().collect();
+        self.released = self.held.difference(&held).cloned().collect();
+        self.held = held;
+        let since_update = &mut self.since_update;
+        since_update.pressed.extend(self.pressed.iter().cloned());
+        since_update.released.extend(self.released.iter().cloned());
+
+        self.axes.clear();
+        self.levels.clear();
+        for (name, bindings) in &self.map.axes {
+            let (mut level, mut delta) = (0.0, 0.0);
+            for binding in bindings {
+                match binding {
+                    AxisBinding::Buttons { .. } => level += self.axis_value(binding, input),
+                    _ => delta += self.axis_value(binding, input),
+                }
+            }
+            self.axes.insert(name.clone(), (level + delta) as f32);
+            self.levels.insert(name.clone(), level);
+            *self.since_update.deltas.entry(name.clone()).or_default() += delta;
+        }
+    }
+
+    fn is_held(&self, binding: &Binding, input: &Input) -> bool {
+        match *binding {
+            Binding::Key(key) => input.key_held(key) || input.key_pressed(key),
+            Binding::Mouse(button) => input.button_held(button) || input.button_pressed(button),
+            Binding::Gesture(gesture) => {
+                self.recognized.contains(&gesture)
+                    || self.long_press.map(long_press_binding) == Some(gesture)
+            }
+        }
+    }
+
+    fn axis_value(&self, binding: &AxisBinding, input: &Input) -> f64 {
+        let pick = |vector: Vector2<f64>, direction: Direction| match direction {
+            Direction::Horizontal => vector.x,
+            Direction::Vertical => vector.y,
+        };
+
+        match *binding {
+            AxisBinding::Buttons { negative, positive } => {
+                let value = |binding| {
+                    if self.is_held(&binding, input) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                };
+                value(positive) - value(negative)
+            }
+            AxisBinding::Scroll(direction) => {
+                let lines = input
+                    .scroll_lines()
+                    .cast()
+                    .unwrap_or(Vector2::new(0.0, 0.0));
+                pick(
+                    lines + input.scroll_pixels() / SCROLL_PIXELS_PER_LINE,
+                    direction,
+                )
+            }
+            AxisBinding::Drag(direction) => pick(self.drag, direction),
+            AxisBinding::MouseMotion(direction) => pick(input.mouse_motion(), direction),
+            AxisBinding::Pinch => self.pinch,
+            AxisBinding::Rotate => self.rotate,
+        }
+    }
+}
+
+fn long_press_binding(after_tap: bool) -> GestureBinding {
+    if after_tap {
+        GestureBinding::TapAndHold
+    } else {
+        GestureBinding::LongPress
+    }
+}
@@ -0,0 +1,778 @@
+use std::collections::{HashMap, VecDeque};
+
+use cgmath::{InnerSpace, Rad, Vector2};
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, MouseButton, TouchPhase, WindowEvent};
+
+use super::PointerId;
+
+/// Thresholds used by [`GestureRecognizer`]. Distances are in physical pixels, durations in
+/// seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GestureConfig {
+    /// Button that acts as a finger, `None` to only recognize touches.
+    pub mouse_button: Option<MouseButton>,
+    /// Longest press that still counts as a tap.
+    pub tap_max_duration: f64,
+    /// How far a press may move before it becomes a drag.
+    pub tap_max_distance: f64,
+    /// Longest time between two taps of a double tap, or between a tap and a long press that
+    /// follows it.
+    pub double_tap_interval: f64,
+    /// How long a press has to stay still to become a long press.
+    pub long_press_duration: f64,
+    /// Slowest release of a drag, in pixels per second, that still flings.
+    pub fling_min_velocity: f64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            mouse_button: Some(MouseButton::Left),
+            tap_max_duration: 0.3,
+            tap_max_distance: 10.0,
+            double_tap_interval: 0.3,
+            long_press_duration: 0.5,
+            fling_min_velocity: 500.0,
+        }
+    }
+}
+
+impl GestureConfig {
+    pub fn with_mouse_button(mut self, mouse_button: Option<MouseButton>) -> Self {
+        self.mouse_button = mouse_button;
+        self
+    }
+
+    pub fn with_tap_max_duration(mut self, tap_max_duration: f64) -> Self {
+        self.tap_max_duration = tap_max_duration;
+        self
+    }
+
+    pub fn with_tap_max_distance(mut self, tap_max_distance: f64) -> Self {
+        self.tap_max_distance = tap_max_distance;
+        self
+    }
+
+    pub fn with_double_tap_interval(mut self, double_tap_interval: f64) -> Self {
+        self.double_tap_interval = double_tap_interval;
+        self
+    }
+
+    pub fn with_long_press_duration(mut self, long_press_duration: f64) -> Self {
+        self.long_press_duration = long_press_duration;
+        self
+    }
+
+    pub fn with_fling_min_velocity(mut self, fling_min_velocity: f64) -> Self {
+        self.fling_min_velocity = fling_min_velocity;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// Emitted on release, so a double tap is preceded by a tap.
+    Tap {
+        position: PhysicalPosition<f64>,
+    },
+    DoubleTap {
+        position: PhysicalPosition<f64>,
+    },
+    /// A press held still for [`GestureConfig::long_press_duration`]. `after_tap` is set if it
+    /// started right after a tap, i.e. tap-and-hold.
+    LongPress {
+        position: PhysicalPosition<f64>,
+        after_tap: bool,
+    },
+    /// The long press was released, moved into a drag or interrupted by a second finger.
+    LongPressEnd {
+        position: PhysicalPosition<f64>,
+    },
+    /// A single finger moved by `delta` since the last drag event.
+    Drag {
+        position: PhysicalPosition<f64>,
+        delta: Vector2<f64>,
+    },
+    DragEnd {
+        position: PhysicalPosition<f64>,
+    },
+    /// A drag was released while moving faster than [`GestureConfig::fling_min_velocity`].
+    /// Follows the `DragEnd`.
+    Fling {
+        position: PhysicalPosition<f64>,
+        velocity: Vector2<f64>,
+    },
+    /// Two fingers moved apart (`scale > 1`) or together since the last pinch event.
+    Pinch {
+        center: PhysicalPosition<f64>,
+        scale: f64,
+    },
+    /// Two fingers turned clockwise (positive `angle`, as y points down) since the last rotate
+    /// event.
+    Rotate {
+        center: PhysicalPosition<f64>,
+        angle: Rad<f64>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Pressed {
+        after_tap: bool,
+    },
+    LongPressing,
+    Dragging,
+    /// Left over from a pinch. Drags once it moves.
+    Remaining,
+    /// Part of a pinch, or left over from a cancelled one.
+    Consumed,
+}
+
+#[derive(Clone, Debug)]
+struct Pointer {
+    start: Vector2<f64>,
+    started_at: Instant,
+    position: Vector2<f64>,
+    phase: Phase,
+    /// Recent positions for estimating the release velocity.
+    samples: VecDeque<(Instant, Vector2<f64>)>,
+}
+
+/// Turns the mouse, touch and cursor events of a window into [`Gesture`]s.
+///
+/// Feed it every [`WindowEvent`] through [`event`](Self::event) and take the recognized gestures
+/// with [`poll`](Self::poll), e.g. in [`AppState::update`](crate::app::AppState::update). Long
+/// presses are only noticed while polling, so they are late in the reactive loop mode until
+/// something else redraws.
+#[derive(Clone, Debug, Default)]
+pub struct GestureRecognizer {
+    pub config: GestureConfig,
+    pointers: HashMap<PointerId, Pointer>,
+    cursor: Option<Vector2<f64>>,
+    last_tap: Option<(Instant, Vector2<f64>)>,
+    /// Distance and angle between the two fingers of the current pinch.
+    pinch: Option<(f64, f64)>,
+    gestures: Vec<Gesture>,
+}
+
+/// Seconds of movement the release velocity is averaged over.
+const VELOCITY_WINDOW: f64 = 0.1;
+/// Shortest time the release velocity is averaged over, so that events arriving in a burst
+/// don't fling at absurd speeds.
+const MIN_VELOCITY_SPAN: f64 = 1.0 / 60.0;
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn event(&mut self, event: &WindowEvent) {
+        self.event_at(event, Instant::now());
+    }
+
+    /// Gestures recognized since the last poll, oldest first.
+    pub fn poll(&mut self) -> std::vec::Drain<'_, Gesture> {
+        self.poll_at(Instant::now())
+    }
+
+    /// Whether any finger, or the mouse button, is down.
+    pub fn is_active(&self) -> bool {
+        !self.pointers.is_empty()
+    }
+
+    fn event_at(&mut self, event: &WindowEvent, now: Instant) {
+        match event {
+            WindowEvent::Touch(touch) => {
+                let id = PointerId::Touch(touch.id);
+                let position = Vector2::new(touch.location.x, touch.location.y);
+                match touch.phase {
+                    TouchPhase::Started => self.down(id, position, now),
+                    TouchPhase::Moved => self.moved(id, position, now),
+                    TouchPhase::Ended => self.up(id, position, now, false),
+                    TouchPhase::Cancelled => self.up(id, position, now, true),
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = Vector2::new(position.x, position.y);
+                self.cursor = Some(position);
+                self.moved(PointerId::Mouse, position, now);
+            }
+            WindowEvent::MouseInput { state, button, .. }
+                if Some(*button) == self.config.mouse_button =>
+            {
+                let Some(position) = self.cursor else {
+                    return;
+                };
+                match state {
+                    ElementState::Pressed => self.down(PointerId::Mouse, position, now),
+                    ElementState::Released => self.up(PointerId::Mouse, position, now, false),
+                }
+            }
+            WindowEvent::Focused(false) => {
+                let ids: Vec<PointerId> = self.pointers.keys().copied().collect();
+                for id in ids {
+                    let position = self.pointers[&id].position;
+                    self.up(id, position, now, true);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn poll_at(&mut self, now: Instant) -> std::vec::Drain<'_, Gesture> {
+        for pointer in self.pointers.values_mut() {
+            if let Phase::Pressed { after_tap } = pointer.phase {
+                if seconds(pointer.started_at, now) >= self.config.long_press_duration {
+                    pointer.phase = Phase::LongPressing;
+                    self.gestures.push(Gesture::LongPress {
+                        position: to_position(pointer.position),
+                        after_tap,
+                    });
+                }
+            }
+        }
+        self.gestures.drain(..)
+    }
+
+    fn down(&mut self, id: PointerId, position: Vector2<f64>, now: Instant) {
+        if self.pointers.contains_key(&id) {
+            return;
+        }
+
+        let after_tap = self.last_tap.is_some_and(|(at, tap_position)| {
+            seconds(at, now) <= self.config.double_tap_interval
+                && (position - tap_position).magnitude() <= self.config.tap_max_distance
+        });
+        let mut phase = Phase::Pressed { after_tap };
+
+        if !self.pointers.is_empty() {
+            phase = Phase::Consumed;
+            let ids: Vec<PointerId> = self.pointers.keys().copied().collect();
+            for id in ids {
+                self.interrupt(id);
+            }
+        }
+
+        self.pointers.insert(
+            id,
+            Pointer {
+                start: position,
+                started_at: now,
+                position,
+                phase,
+                samples: VecDeque::from([(now, position)]),
+            },
+        );
+        self.pinch = self.pinch_shape();
+    }
+
+    fn moved(&mut self, id: PointerId, position: Vector2<f64>, now: Instant) {
+        let tap_max_distance = self.config.tap_max_distance;
+        let Some(pointer) = self.pointers.get_mut(&id) else {
+            return;
+        };
+
+        let last = pointer.position;
+        pointer.position = position;
+        pointer.samples.push_back((now, position));
+        while pointer
+            .samples
+            .front()
+            .is_some_and(|(at, _)| seconds(*at, now) > VELOCITY_WINDOW)
+        {
+            pointer.samples.pop_front();
+        }
+
+        match pointer.phase {
+            Phase::Pressed { .. } | Phase::LongPressing => {
+                if (position - pointer.start).magnitude() > tap_max_distance {
+                    if pointer.phase == Phase::LongPressing {
+                        self.gestures.push(Gesture::LongPressEnd {
+                            position: to_position(last),
+                        });
+                    }
+                    pointer.phase = Phase::Dragging;
+                    self.gestures.push(Gesture::Drag {
+                        position: to_position(position),
+                        delta: position - pointer.start,
+                    });
+                }
+            }
+            Phase::Remaining | Phase::Dragging => {
+                pointer.phase = Phase::Dragging;
+                self.gestures.push(Gesture::Drag {
+                    position: to_position(position),
+                    delta: position - last,
+                });
+            }
+            Phase::Consumed => self.pinch_moved(),
+        }
+    }
+
+    fn up(&mut self, id: PointerId, position: Vector2<f64>, now: Instant, cancelled: bool) {
+        let Some(pointer) = self.pointers.remove(&id) else {
+            return;
+        };
+
+        match pointer.phase {
+            Phase::Pressed { .. }
+                if !cancelled
+                    && seconds(pointer.started_at, now) <= self.config.tap_max_duration =>
+            {
+                self.tapped(position, now)
+            }
+            Phase::LongPressing => self.gestures.push(Gesture::LongPressEnd {
+                position: to_position(position),
+            }),
+            Phase::Dragging => {
+                self.gestures.push(Gesture::DragEnd {
+                    position: to_position(position),
+                });
+                let velocity = release_velocity(&pointer.samples, position, now);
+                if !cancelled && velocity.magnitude() >= self.config.fling_min_velocity {
+                    self.gestures.push(Gesture::Fling {
+                        position: to_position(position),
+                        velocity,
+                    });
+                }
+            }
+            _ => (),
+        }
+
+        // A finger left over from a pinch drags from where it is now, unless the pinch was
+        // cancelled, e.g. by losing the focus
+        if let [remaining] = self
+            .pointers
+            .values_mut()
+            .collect::<Vec<_>>()
+            .as_mut_slice()
+        {
+            if !cancelled {
+                remaining.phase = Phase::Remaining;
+                remaining.samples = VecDeque::from([(now, remaining.position)]);
+            }
+        }
+        self.pinch = self.pinch_shape();
+    }
+
+    fn tapped(&mut self, position: Vector2<f64>, now: Instant) {
+        let double = self.last_tap.is_some_and(|(at, tap_position)| {
+            seconds(at, now) <= self.config.double_tap_interval
+                && (position - tap_position).magnitude() <= self.config.tap_max_distance
+        });
+
+        if double {
+            self.last_tap = None;
+            self.gestures.push(Gesture::DoubleTap {
+                position: to_position(position),
+            });
+        } else {
+            self.last_tap = Some((now, position));
+            self.gestures.push(Gesture::Tap {
+                position: to_position(position),
+            });
+        }
+    }
+
+    /// Ends whatever a pointer was doing on its own because another finger joined it.
+    fn interrupt(&mut self, id: PointerId) {
+        let Some(pointer) = self.pointers.get_mut(&id) else {
+            return;
+        };
+        let position = to_position(pointer.position);
+        match pointer.phase {
+            Phase::LongPressing => self.gestures.push(Gesture::LongPressEnd { position }),
+            Phase::Dragging => self.gestures.push(Gesture::DragEnd { position }),
+            _ => (),
+        }
+        pointer.phase = Phase::Consumed;
+    }
+
+    fn pinch_moved(&mut self) {
+        let (Some((last_distance, last_angle)), Some((distance, angle))) =
+            (self.pinch, self.pinch_shape())
+        else {
+            return;
+        };
+        self.pinch = Some((distance, angle));
+
+        let center = to_position(self.pinch_center());
+        if last_distance > 0.0 && distance != last_distance {
+            self.gestures.push(Gesture::Pinch {
+                center,
+                scale: distance / last_distance,
+            });
+        }
+        if angle != last_angle {
+            let turn = std::f64::consts::TAU;
+            let angle = (angle - last_angle + turn / 2.0).rem_euclid(turn) - turn / 2.0;
+            self.gestures.push(Gesture::Rotate {
+                center,
+                angle: Rad(angle),
+            });
+        }
+    }
+
+    /// Distance and angle between the two fingers, if there are exactly two. Ordered by id so
+    /// that the angle doesn't flip when they cross.
+    fn pinch_shape(&self) -> Option<(f64, f64)> {
+        let mut pointers: Vec<_> = self.pointers.iter().collect();
+        pointers.sort_by_key(|(id, _)| **id);
+        let [(_, a), (_, b)] = pointers.as_slice() else {
+            return None;
+        };
+        let offset = b.position - a.position;
+        Some((offset.magnitude(), offset.y.atan2(offset.x)))
+    }
+
+    fn pinch_center(&self) -> Vector2<f64> {
+        let sum = self
+            .pointers
+            .values()
+            .fold(Vector2::new(0.0, 0.0), |sum, pointer| {
+                sum + pointer.position
+            });
+        sum / self.pointers.len().max(1) as f64
+    }
+}
+
+fn release_velocity(
+    samples: &VecDeque<(Instant, Vector2<f64>)>,
+    position: Vector2<f64>,
+    now: Instant,
+) -> Vector2<f64> {
+    match samples.front() {
+        Some((at, oldest)) => (position - oldest) / seconds(*at, now).max(MIN_VELOCITY_SPAN),
+        None => Vector2::new(0.0, 0.0),
+    }
+}
+
+fn seconds(from: Instant, to: Instant) -> f64 {
+    to.saturating_duration_since(from).as_secs_f64()
+}
+
+fn to_position(position: Vector2<f64>) -> PhysicalPosition<f64> {
+    PhysicalPosition::new(position.x, position.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use winit::event::{DeviceId, Touch};
+
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f64, y: f64) -> WindowEvent {
+        WindowEvent::Touch(Touch {
+            // SAFETY: only compared, never passed to the platform
+            device_id: unsafe { DeviceId::dummy() },
+            phase,
+            location: PhysicalPosition::new(x, y),
+            force: None,
+            id,
+        })
+    }
+
+    /// Feeds `events`, each with its time in milliseconds after `start`, and polls right after
+    /// the last one.
+    fn run(
+        recognizer: &mut GestureRecognizer,
+        start: Instant,
+        events: &[(u64, WindowEvent)],
+    ) -> Vec<Gesture> {
+        let mut now = start;
+        for (ms, event) in events {
+            now = start + Duration::from_millis(*ms);
+            recognizer.event_at(event, now);
+        }
+        recognizer.poll_at(now).collect()
+    }
+
+    fn position(x: f64, y: f64) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(x, y)
+    }
+
+    #[test]
+    fn tap_and_double_tap() {
+        let mut recognizer = GestureRecognizer::default();
+        let start = Instant::now();
+
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (0, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (100, touch(1, TouchPhase::Ended, 0.0, 0.0)),
+                (200, touch(1, TouchPhase::Started, 5.0, 0.0)),
+                (250, touch(1, TouchPhase::Ended, 5.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            [
+                Gesture::Tap {
+                    position: position(0.0, 0.0)
+                },
+                Gesture::DoubleTap {
+                    position: position(5.0, 0.0)
+                },
+            ]
+        );
+
+        // Too late for a double tap
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (1000, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (1050, touch(1, TouchPhase::Ended, 0.0, 0.0)),
+                (1500, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (1550, touch(1, TouchPhase::Ended, 0.0, 0.0)),
+            ],
+        );
+        let tap = Gesture::Tap {
+            position: position(0.0, 0.0),
+        };
+        assert_eq!(gestures, [tap, tap]);
+    }
+
+    #[test]
+    fn long_press_and_tap_and_hold() {
+        let mut recognizer = GestureRecognizer::default();
+        let start = Instant::now();
+
+        recognizer.event_at(&touch(1, TouchPhase::Started, 0.0, 0.0), start);
+        let at = |ms| start + Duration::from_millis(ms);
+        assert_eq!(recognizer.poll_at(at(400)).count(), 0);
+        assert_eq!(
+            recognizer.poll_at(at(500)).collect::<Vec<_>>(),
+            [Gesture::LongPress {
+                position: position(0.0, 0.0),
+                after_tap: false
+            }]
+        );
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[(600, touch(1, TouchPhase::Ended, 0.0, 0.0))],
+        );
+        assert_eq!(
+            gestures,
+            [Gesture::LongPressEnd {
+                position: position(0.0, 0.0)
+            }]
+        );
+
+        run(
+            &mut recognizer,
+            start,
+            &[
+                (1000, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (1050, touch(1, TouchPhase::Ended, 0.0, 0.0)),
+                (1200, touch(1, TouchPhase::Started, 0.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            recognizer.poll_at(at(1700)).collect::<Vec<_>>(),
+            [Gesture::LongPress {
+                position: position(0.0, 0.0),
+                after_tap: true
+            }]
+        );
+    }
+
+    #[test]
+    fn drag_starts_after_slop() {
+        let mut recognizer = GestureRecognizer::default();
+        let start = Instant::now();
+
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (0, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (100, touch(1, TouchPhase::Moved, 5.0, 0.0)),
+            ],
+        );
+        assert_eq!(gestures, []);
+
+        // The first drag covers the slop as well
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (200, touch(1, TouchPhase::Moved, 15.0, 0.0)),
+                (300, touch(1, TouchPhase::Moved, 20.0, 0.0)),
+                (1000, touch(1, TouchPhase::Ended, 20.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            [
+                Gesture::Drag {
+                    position: position(15.0, 0.0),
+                    delta: Vector2::new(15.0, 0.0)
+                },
+                Gesture::Drag {
+                    position: position(20.0, 0.0),
+                    delta: Vector2::new(5.0, 0.0)
+                },
+                Gesture::DragEnd {
+                    position: position(20.0, 0.0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finger_left_over_from_pinch_drags() {
+        let mut recognizer = GestureRecognizer::default();
+        let start = Instant::now();
+
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (0, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (10, touch(2, TouchPhase::Started, 100.0, 0.0)),
+                (100, touch(2, TouchPhase::Moved, 200.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            [Gesture::Pinch {
+                center: position(100.0, 0.0),
+                scale: 2.0
+            }]
+        );
+
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (200, touch(2, TouchPhase::Ended, 200.0, 0.0)),
+                (300, touch(1, TouchPhase::Moved, 3.0, 0.0)),
+                (400, touch(1, TouchPhase::Ended, 3.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            [
+                Gesture::Drag {
+                    position: position(3.0, 0.0),
+                    delta: Vector2::new(3.0, 0.0)
+                },
+                Gesture::DragEnd {
+                    position: position(3.0, 0.0)
+                },
+            ]
+        );
+        assert!(!recognizer.is_active());
+
+        // Without moving, the left over finger never drags
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (1000, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (1010, touch(2, TouchPhase::Started, 100.0, 0.0)),
+                (1100, touch(2, TouchPhase::Ended, 100.0, 0.0)),
+                (1200, touch(1, TouchPhase::Ended, 0.0, 0.0)),
+            ],
+        );
+        assert_eq!(gestures, []);
+    }
+
+    #[test]
+    fn fling_needs_min_velocity() {
+        let drag = |scale: u64| {
+            let mut recognizer = GestureRecognizer::default();
+            run(
+                &mut recognizer,
+                Instant::now(),
+                &[
+                    (0, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                    (100 / scale, touch(1, TouchPhase::Moved, 20.0, 0.0)),
+                    (150 / scale, touch(1, TouchPhase::Moved, 30.0, 0.0)),
+                    (200 / scale, touch(1, TouchPhase::Moved, 40.0, 0.0)),
+                    (200 / scale, touch(1, TouchPhase::Ended, 40.0, 0.0)),
+                ],
+            )
+        };
+
+        // 200 pixels per second
+        let gestures = drag(1);
+        assert!(matches!(gestures.last(), Some(Gesture::DragEnd { .. })));
+
+        // 2000 pixels per second
+        let gestures = drag(10);
+        let Some(Gesture::Fling { velocity, .. }) = gestures.last() else {
+            panic!("no fling in {:?}", gestures);
+        };
+        assert!(velocity.x >= 500.0 && velocity.y == 0.0);
+    }
+
+    #[test]
+    fn losing_focus_cancels() {
+        let mut recognizer = GestureRecognizer::default();
+        let start = Instant::now();
+
+        // A fast drag doesn't fling and a short press doesn't tap
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (0, touch(1, TouchPhase::Started, 0.0, 0.0)),
+                (10, touch(1, TouchPhase::Moved, 100.0, 0.0)),
+                (20, WindowEvent::Focused(false)),
+                (100, touch(2, TouchPhase::Started, 0.0, 0.0)),
+                (150, WindowEvent::Focused(false)),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            [
+                Gesture::Drag {
+                    position: position(100.0, 0.0),
+                    delta: Vector2::new(100.0, 0.0)
+                },
+                Gesture::DragEnd {
+                    position: position(100.0, 0.0)
+                },
+            ]
+        );
+        assert!(!recognizer.is_active());
+
+        // Two fingers are cancelled without dragging
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (160, touch(3, TouchPhase::Started, 0.0, 0.0)),
+                (170, touch(4, TouchPhase::Started, 100.0, 0.0)),
+                (180, WindowEvent::Focused(false)),
+            ],
+        );
+        assert_eq!(gestures, []);
+        assert!(!recognizer.is_active());
+
+        // Ending the touches afterwards does nothing
+        let gestures = run(
+            &mut recognizer,
+            start,
+            &[
+                (200, touch(1, TouchPhase::Ended, 100.0, 0.0)),
+                (200, touch(2, TouchPhase::Ended, 0.0, 0.0)),
+            ],
+        );
+        assert_eq!(gestures, []);
+    }
+}
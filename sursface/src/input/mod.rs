@@ -0,0 +1,197 @@
+pub mod action;
+pub mod gesture;
+pub mod pointer;
+
+pub use action::{ActionMap, Actions, AxisBinding, Binding, Direction, GestureBinding};
+pub use gesture::{Gesture, GestureConfig, GestureRecognizer};
+pub use pointer::{PointerEvent, PointerId, PointerKind, PointerPhase};
+
+use std::collections::HashSet;
+
+use cgmath::{Vector2, Zero};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceEvent, ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+/// Snapshot of the keyboard and mouse state of a window, kept up to date by the app in
+/// [`Display::input`](crate::display::Display::input).
+///
+/// Keys and buttons pressed or released, and the cursor, scroll and motion deltas, cover
+/// everything since the window last drew, and are reset right after it draws.
+#[derive(Clone, Debug)]
+pub struct Input {
+    keys_held: HashSet<KeyCode>,
+    keys_pressed: HashSet<KeyCode>,
+    keys_released: HashSet<KeyCode>,
+    buttons_held: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    cursor_position: Option<PhysicalPosition<f64>>,
+    /// Kept when the cursor leaves, for the mouse pointer events released outside the window.
+    /// The origin until the cursor first moves.
+    last_cursor_position: PhysicalPosition<f64>,
+    cursor_delta: Vector2<f64>,
+    scroll_lines: Vector2<f32>,
+    scroll_pixels: Vector2<f64>,
+    mouse_motion: Vector2<f64>,
+    modifiers: ModifiersState,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self {
+            keys_held: HashSet::new(),
+            keys_pressed: HashSet::new(),
+            keys_released: HashSet::new(),
+            buttons_held: HashSet::new(),
+            buttons_pressed: HashSet::new(),
+            buttons_released: HashSet::new(),
+            cursor_position: None,
+            last_cursor_position: PhysicalPosition::new(0.0, 0.0),
+            cursor_delta: Vector2::zero(),
+            scroll_lines: Vector2::zero(),
+            scroll_pixels: Vector2::zero(),
+            mouse_motion: Vector2::zero(),
+            modifiers: ModifiersState::empty(),
+        }
+    }
+}
+
+impl Input {
+    pub fn key_held(&self, key: KeyCode) -> bool {
+        self.keys_held.contains(&key)
+    }
+
+    /// Whether the key went down this frame. Key repeats don't count.
+    pub fn key_pressed(&self, key: KeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn key_released(&self, key: KeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn keys_held(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.keys_held.iter().copied()
+    }
+
+    pub fn button_held(&self, button: MouseButton) -> bool {
+        self.buttons_held.contains(&button)
+    }
+
+    pub fn button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn button_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Position of the cursor in the window, `None` while it is outside.
+    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor_position
+    }
+
+    /// How far the cursor moved in the window this frame, in physical pixels.
+    pub fn cursor_delta(&self) -> Vector2<f64> {
+        self.cursor_delta
+    }
+
+    /// Scrolling this frame in lines, from mouse wheels that scroll line by line.
+    pub fn scroll_lines(&self) -> Vector2<f32> {
+        self.scroll_lines
+    }
+
+    /// Scrolling this frame in physical pixels, from touchpads and other precise devices.
+    pub fn scroll_pixels(&self) -> Vector2<f64> {
+        self.scroll_pixels
+    }
+
+    /// Raw mouse motion this frame, unaffected by cursor acceleration or the window edges.
+    /// Only tracked on the main window.
+    pub fn mouse_motion(&self) -> Vector2<f64> {
+        self.mouse_motion
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub(crate) fn window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                let PhysicalKey::Code(key) = event.physical_key else {
+                    return;
+                };
+                match event.state {
+                    ElementState::Pressed => {
+                        if self.keys_held.insert(key) {
+                            self.keys_pressed.insert(key);
+                        }
+                    }
+                    ElementState::Released => {
+                        if self.keys_held.remove(&key) {
+                            self.keys_released.insert(key);
+                        }
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    if self.buttons_held.insert(*button) {
+                        self.buttons_pressed.insert(*button);
+                    }
+                }
+                ElementState::Released => {
+                    if self.buttons_held.remove(button) {
+                        self.buttons_released.insert(*button);
+                    }
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                if let Some(last) = self.cursor_position {
+                    self.cursor_delta += Vector2::new(position.x - last.x, position.y - last.y);
+                }
+                self.cursor_position = Some(*position);
+                self.last_cursor_position = *position;
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+            }
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => self.scroll_lines += Vector2::new(*x, *y),
+                MouseScrollDelta::PixelDelta(delta) => {
+                    self.scroll_pixels += Vector2::new(delta.x, delta.y)
+                }
+            },
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            // Releases would go to whichever window has the focus now
+            WindowEvent::Focused(false) => {
+                self.keys_released.extend(self.keys_held.drain());
+                self.buttons_released.extend(self.buttons_held.drain());
+                self.modifiers = ModifiersState::empty();
+            }
+            _ => (),
+        }
+    }
+
+    pub(crate) fn device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            self.mouse_motion += Vector2::new(*x, *y);
+        }
+    }
+
+    /// Forgets the edges and deltas of the frame that was just drawn.
+    pub(crate) fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.cursor_delta = Vector2::zero();
+        self.scroll_lines = Vector2::zero();
+        self.scroll_pixels = Vector2::zero();
+        self.mouse_motion = Vector2::zero();
+    }
+}
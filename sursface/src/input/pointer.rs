@@ -0,0 +1,141 @@
+use winit::dpi::{LogicalPosition, PhysicalPosition};
+use winit::event::{ElementState, Force, MouseButton, TouchPhase, WindowEvent};
+
+use super::Input;
+
+/// Identifies a pointer for as long as it is down. Every finger keeps its id from touching the
+/// screen until it is lifted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PointerId {
+    Mouse,
+    Touch(u64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerKind {
+    Mouse,
+    Touch,
+    /// A stylus, as far as the platform tells it apart from a finger. Currently only the Apple
+    /// Pencil on iOS.
+    Pen,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerPhase {
+    /// A finger or pen touched the screen, or the first mouse button was pressed.
+    Down,
+    /// Also sent for a mouse that moves without any button held.
+    Move,
+    /// The pointer was lifted, or the last mouse button was released.
+    Up,
+    /// The platform took the pointer away, e.g. for a system gesture, or the window lost the
+    /// focus while a mouse button was held. No `Up` follows.
+    Cancel,
+}
+
+/// Mouse, touch and pen input in one shape, delivered through
+/// [`AppState::pointer_event`](crate::app::AppState::pointer_event).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointerEvent {
+    pub id: PointerId,
+    pub kind: PointerKind,
+    pub phase: PointerPhase,
+    /// For mouse buttons released outside the window, where the cursor was last seen. The
+    /// origin for buttons pressed before the cursor first moved.
+    pub position: PhysicalPosition<f64>,
+    pub logical_position: LogicalPosition<f64>,
+    /// From 0 to 1. Like on the web, it is 0.5 for pointers down on hardware without pressure
+    /// sensing and 0 for pointers that aren't down.
+    pub pressure: f64,
+    /// The mouse button that went down or up, for mouse `Down` and `Up` events.
+    pub button: Option<MouseButton>,
+}
+
+impl PointerEvent {
+    /// Translates a window event, given the input state from before it.
+    pub(crate) fn from_window_event(
+        event: &WindowEvent,
+        input: &Input,
+        scale_factor: f64,
+    ) -> Option<Self> {
+        let mouse_down = !input.buttons_held.is_empty();
+        match event {
+            WindowEvent::Touch(touch) => {
+                let kind = match touch.force {
+                    Some(Force::Calibrated {
+                        altitude_angle: Some(_),
+                        ..
+                    }) => PointerKind::Pen,
+                    _ => PointerKind::Touch,
+                };
+                let phase = match touch.phase {
+                    TouchPhase::Started => PointerPhase::Down,
+                    TouchPhase::Moved => PointerPhase::Move,
+                    TouchPhase::Ended => PointerPhase::Up,
+                    TouchPhase::Cancelled => PointerPhase::Cancel,
+                };
+                let pressure = match phase {
+                    PointerPhase::Down | PointerPhase::Move => {
+                        touch.force.map_or(0.5, |force| force.normalized())
+                    }
+                    PointerPhase::Up | PointerPhase::Cancel => 0.0,
+                };
+                Some(Self {
+                    id: PointerId::Touch(touch.id),
+                    kind,
+                    phase,
+                    position: touch.location,
+                    logical_position: touch.location.to_logical(scale_factor),
+                    pressure,
+                    button: None,
+                })
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let pressure = if mouse_down { 0.5 } else { 0.0 };
+                let event = Self::mouse(PointerPhase::Move, *position, scale_factor, pressure);
+                Some(event)
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let position = input.last_cursor_position;
+                let (phase, pressure) = match state {
+                    ElementState::Pressed if !mouse_down => (PointerPhase::Down, 0.5),
+                    ElementState::Released
+                        if input.buttons_held.len() == 1 && input.button_held(*button) =>
+                    {
+                        (PointerPhase::Up, 0.0)
+                    }
+                    // Chorded buttons only change the state of a mouse that is already down
+                    _ => return None,
+                };
+                Some(Self {
+                    button: Some(*button),
+                    ..Self::mouse(phase, position, scale_factor, pressure)
+                })
+            }
+            WindowEvent::Focused(false) if mouse_down => Some(Self::mouse(
+                PointerPhase::Cancel,
+                input.last_cursor_position,
+                scale_factor,
+                0.0,
+            )),
+            _ => None,
+        }
+    }
+
+    fn mouse(
+        phase: PointerPhase,
+        position: PhysicalPosition<f64>,
+        scale_factor: f64,
+        pressure: f64,
+    ) -> Self {
+        Self {
+            id: PointerId::Mouse,
+            kind: PointerKind::Mouse,
+            phase,
+            position,
+            logical_position: position.to_logical(scale_factor),
+            pressure,
+            button: None,
+        }
+    }
+}
@@ -1,7 +1,13 @@
-pub use {cgmath, image, log, web_time, wgpu, winit};
+pub use {cgmath, fern, image, log, web_time, wgpu, winit};
 
 pub mod app;
 pub mod display;
+pub mod error;
+pub mod input;
+pub mod logging;
+pub mod pacing;
 pub mod start;
 pub mod std;
 pub mod time;
+
+pub use error::Error;
@@ -0,0 +1,155 @@
+use std::sync::Once;
+
+use log::LevelFilter;
+
+#[cfg(target_arch = "wasm32")]
+extern crate console_error_panic_hook;
+
+/// How the app installs its global logger, returned by
+/// [`AppState::logging`](crate::app::AppState::logging).
+///
+/// By default, logs at `Info` and above go to stderr on desktop, where `RUST_LOG` is applied on
+/// top, and to the browser console on the web, while wgpu and naga only log errors.
+pub struct Logging {
+    enabled: bool,
+    level: LevelFilter,
+    module_levels: Vec<(String, LevelFilter)>,
+    chains: Vec<fern::Output>,
+    #[cfg(feature = "tracing")]
+    tracing: bool,
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        let wgpu_level = LevelFilter::Error;
+
+        Self {
+            enabled: true,
+            level: LevelFilter::Info,
+            module_levels: vec![
+                ("wgpu_core".to_string(), wgpu_level),
+                ("wgpu_hal".to_string(), wgpu_level),
+                ("naga".to_string(), wgpu_level),
+            ],
+            chains: Vec::new(),
+            #[cfg(feature = "tracing")]
+            tracing: false,
+        }
+    }
+}
+
+impl Logging {
+    /// Leaves installing a logger to the app, e.g. because it already did before starting
+    /// sursface.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Overrides the level of a module and its children, e.g. `"wgpu_core"`.
+    pub fn with_module_level(mut self, module: impl Into<String>, level: LevelFilter) -> Self {
+        let module = module.into();
+        self.module_levels
+            .retain(|(existing, _)| *existing != module);
+        self.module_levels.push((module, level));
+        self
+    }
+
+    /// Sends the logs to another output as well, e.g. `fern::log_file("app.log")?` on desktop.
+    pub fn with_chain(mut self, output: impl Into<fern::Output>) -> Self {
+        self.chains.push(output.into());
+        self
+    }
+
+    /// Forwards the logs to `tracing` as events, for apps that install a `tracing` subscriber.
+    #[cfg(feature = "tracing")]
+    pub fn with_tracing(mut self, tracing: bool) -> Self {
+        self.tracing = tracing;
+        self
+    }
+
+    fn dispatch(&self) -> fern::Dispatch {
+        self.module_levels.iter().fold(
+            fern::Dispatch::new().level(self.level),
+            |dispatch, (module, level)| dispatch.level_for(module.clone(), *level),
+        )
+    }
+
+    /// The most verbose of the configured levels.
+    fn max_level(&self) -> LevelFilter {
+        self.module_levels
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, Ord::max)
+    }
+
+    /// The console output and the most verbose level it lets through.
+    #[cfg(target_arch = "wasm32")]
+    fn console(&self) -> (fern::Output, LevelFilter) {
+        let output = self
+            .dispatch()
+            .chain(fern::Output::call(console_log::log))
+            .into();
+        (output, self.max_level())
+    }
+
+    /// The console output and the most verbose level it lets through, which `RUST_LOG` can raise.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn console(&self) -> (fern::Output, LevelFilter) {
+        // parse_default_env will read the RUST_LOG environment variable and apply it on top
+        // of these default filters
+        let mut builder = env_logger::builder();
+        builder.filter_level(self.level);
+        for (module, level) in &self.module_levels {
+            builder.filter_module(module, *level);
+        }
+        let logger = builder.parse_default_env().build();
+        let level = logger.filter();
+        let logger: Box<dyn log::Log> = Box::new(logger);
+        (logger.into(), level)
+    }
+
+    fn apply(mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        // Without a level, fern lets everything through to the boxed outputs and log macros
+        // can't skip disabled records early
+        let (console, console_level) = self.console();
+        let mut root = fern::Dispatch::new()
+            .level(console_level.max(self.max_level()))
+            .chain(console);
+        #[cfg(feature = "tracing")]
+        if self.tracing {
+            let tracer: Box<dyn log::Log> = Box::new(tracing_log::LogTracer::new());
+            root = root.chain(self.dispatch().chain(tracer));
+        }
+        for output in std::mem::take(&mut self.chains) {
+            root = root.chain(self.dispatch().chain(output));
+        }
+
+        if let Err(err) = root.apply() {
+            log::warn!("Couldn't install logger: {}", err);
+        }
+    }
+}
+
+/// Initializes logging the first time it is called, so later `resumed` calls keep the logger
+/// installed by the first one.
+pub(crate) fn init(logging: impl FnOnce() -> Logging) {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        #[cfg(target_arch = "wasm32")]
+        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+
+        logging().apply();
+    });
+}
@@ -0,0 +1,122 @@
+use web_time::{Duration, Instant};
+use winit::event::WindowEvent;
+use winit::event_loop::ControlFlow;
+
+/// How often the app redraws its windows. Set initially by
+/// [`AppState::loop_mode`](crate::app::AppState::loop_mode) and changed at runtime through
+/// [`Display::set_loop_mode`](crate::display::Display::set_loop_mode).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoopMode {
+    /// Redraws all the time. Without a target frame rate, every window redraws as soon as it
+    /// has drawn, paced by the present mode. With one, the event loop sleeps until the next
+    /// frame is due, skipping frames it is late for instead of catching up.
+    Continuous { target_fps: Option<f64> },
+    /// Redraws a window only after input on it, after it was resized or uncovered, or when
+    /// [`Display::request_redraw`](crate::display::Display::request_redraw) is called. The event
+    /// loop sleeps in between.
+    Reactive,
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Continuous { target_fps: None }
+    }
+}
+
+impl LoopMode {
+    pub fn vsync() -> Self {
+        LoopMode::Continuous { target_fps: None }
+    }
+
+    pub fn target_fps(fps: f64) -> Self {
+        LoopMode::Continuous {
+            target_fps: Some(fps),
+        }
+    }
+}
+
+pub(crate) struct Pacer {
+    mode: LoopMode,
+    next_frame: Option<Instant>,
+}
+
+impl Pacer {
+    pub fn new(mode: LoopMode) -> Self {
+        Self {
+            mode,
+            next_frame: None,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: LoopMode) {
+        log::debug!("Loop mode set to {:?}", mode);
+        self.mode = mode;
+        self.next_frame = None;
+    }
+
+    /// Whether a window should request its next redraw as soon as it has drawn.
+    pub fn redraw_after_draw(&self) -> bool {
+        self.mode == LoopMode::Continuous { target_fps: None }
+    }
+
+    /// Whether the window that received `event` should redraw because of it.
+    pub fn redraw_after_event(&self, event: &WindowEvent) -> bool {
+        self.mode == LoopMode::Reactive && is_redraw_trigger(event)
+    }
+
+    /// Picks how long the event loop may sleep, and whether the windows are due for a redraw
+    /// right now.
+    pub fn schedule(&mut self, now: Instant) -> (ControlFlow, bool) {
+        let LoopMode::Continuous {
+            target_fps: Some(fps),
+        } = self.mode
+        else {
+            return (ControlFlow::Wait, false);
+        };
+        if fps <= 0.0 || !fps.is_finite() {
+            return (ControlFlow::Wait, false);
+        }
+
+        let interval = Duration::from_secs_f64(1.0 / fps);
+        let next_frame = self.next_frame.unwrap_or(now);
+        if now < next_frame {
+            return (ControlFlow::WaitUntil(next_frame), false);
+        }
+
+        let next_frame = if now - next_frame > interval {
+            now + interval
+        } else {
+            next_frame + interval
+        };
+        self.next_frame = Some(next_frame);
+        (ControlFlow::WaitUntil(next_frame), true)
+    }
+}
+
+fn is_redraw_trigger(event: &WindowEvent) -> bool {
+    matches!(
+        event,
+        WindowEvent::Resized(_)
+            | WindowEvent::ScaleFactorChanged { .. }
+            | WindowEvent::Focused(_)
+            | WindowEvent::Occluded(false)
+            | WindowEvent::ThemeChanged(_)
+            | WindowEvent::KeyboardInput { .. }
+            | WindowEvent::ModifiersChanged(_)
+            | WindowEvent::Ime(_)
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::CursorEntered { .. }
+            | WindowEvent::CursorLeft { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::PinchGesture { .. }
+            | WindowEvent::PanGesture { .. }
+            | WindowEvent::DoubleTapGesture { .. }
+            | WindowEvent::RotationGesture { .. }
+            | WindowEvent::TouchpadPressure { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::DroppedFile(_)
+            | WindowEvent::HoveredFile(_)
+            | WindowEvent::HoveredFileCancelled
+    )
+}
@@ -1,9 +1,17 @@
-use winit::event_loop::{ControlFlow, EventLoop};
+#[cfg(not(target_arch = "wasm32"))]
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
 
 #[cfg(target_arch = "wasm32")]
 use wgpu::web_sys::HtmlCanvasElement;
 
-use crate::app::{App, AppState};
+use crate::app::{App, AppEvent, AppState};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::display::Display;
+use crate::display::WindowOptions;
+use crate::error::Error;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::time::FrameClock;
 
 #[cfg(target_arch = "wasm32")]
 extern crate console_error_panic_hook;
@@ -11,19 +19,79 @@ extern crate console_error_panic_hook;
 #[cfg(not(target_arch = "wasm32"))]
 pub fn create_window_desktop<State: AppState + 'static>(
     width: u32, height: u32,
-) {
-    let event_loop = EventLoop::new().unwrap();
-    event_loop.set_control_flow(ControlFlow::Poll);
+) -> Result<(), Error> {
+    create_window_desktop_with_options::<State>(
+        WindowOptions::default().with_inner_size(PhysicalSize::new(width, height)),
+    )
+}
 
-    let mut app = App::<State>::from_window_size(width, height);
-    event_loop.run_app(&mut app).unwrap();
+#[cfg(not(target_arch = "wasm32"))]
+pub fn create_window_desktop_with_options<State: AppState + 'static>(
+    options: WindowOptions,
+) -> Result<(), Error> {
+    run::<State>(options)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn create_window_browser<State: AppState + 'static>(
+    canvas: HtmlCanvasElement,
+) -> Result<(), Error> {
+    create_window_browser_with_options::<State>(WindowOptions::default().with_canvas(canvas))
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn create_window_browser<State: AppState + 'static>(canvas: HtmlCanvasElement) {
-    let event_loop = EventLoop::new().unwrap();
-    event_loop.set_control_flow(ControlFlow::Poll);
+pub fn create_window_browser_with_options<State: AppState + 'static>(
+    options: WindowOptions,
+) -> Result<(), Error> {
+    run::<State>(options)
+}
+
+/// Runs a single frame of `State` without a window and returns the rendered image. The frame
+/// format is picked by the `surface_format` of `State::display_config` among the 8-bit RGBA and
+/// BGRA formats that can be read back. To run without a GPU, e.g. on CI, the backends of
+/// `State::display_config` have to include `Backends::GL`, see [`Display::headless_with_config`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_headless<State: AppState>(
+    width: u32, height: u32,
+) -> Result<image::RgbaImage, Error> {
+    let config = State::display_config();
+    let format = config.surface_format.select(&[
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Bgra8Unorm,
+    ]);
+
+    let mut display = Display::headless_with_config(width, height, format, &config)?;
+    let mut state = State::new(&mut display);
+    let mut clock = FrameClock::new(State::timestep());
+    clock.tick(web_time::Instant::now());
+    state.draw(&mut display, &mut clock);
+    if let Some(err) = display.take_fatal_error() {
+        return Err(err);
+    }
+    display.read_frame()
+}
+
+fn run<State: AppState + 'static>(options: WindowOptions) -> Result<(), Error> {
+    let mut app_error = None;
+    let result = EventLoop::<AppEvent>::with_user_event()
+        .build()
+        .and_then(|event_loop| {
+            let mut app = App::<State>::new(options, event_loop.create_proxy());
+            let result = event_loop.run_app(&mut app);
+            app_error = app.error.take();
+            result
+        });
+
+    if let Err(err) = result {
+        let err = Error::from(err);
+        State::fatal_error(&err);
+        return Err(err);
+    }
 
-    let mut app = App::<State>::from_canvas(canvas);
-    event_loop.run_app(&mut app).unwrap();
+    match app_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
 }
@@ -4,37 +4,74 @@ use wgpu::{
     BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, Buffer, Color,
     CommandEncoder, Device, Face, FrontFace, PipelineLayout, PolygonMode, PrimitiveState,
     PrimitiveTopology, Queue, RenderPass, RenderPipeline, Sampler, ShaderModule, Surface,
-    SurfaceTexture, TextureView, VertexBufferLayout,
+    SurfaceTexture, TextureFormat, TextureView, VertexBufferLayout,
 };
 
 use crate::display::Display;
+use crate::error::Error;
 
 pub mod models;
 
-pub fn get_framebuffer(surface: &Surface) -> (SurfaceTexture, TextureView) {
-    let output = surface.get_current_texture().unwrap();
+pub fn get_framebuffer(surface: &Surface) -> Result<(SurfaceTexture, TextureView), Error> {
+    let output = surface.get_current_texture()?;
     let view = output
         .texture
         .create_view(&wgpu::TextureViewDescriptor::default());
-    (output, view)
+    Ok((output, view))
 }
 
+/// Begins a render pass that clears `view` to `color`. If `depth` is set and the display owns a
+/// depth buffer, it is attached and cleared as well, e.g. unset for an overlay pass. If the
+/// display is multisampled, the pass renders into its MSAA target and resolves to `view` at the
+/// end, through the MSAA view in `format`, the format of `view`.
 pub fn clear<'a>(
+    display: &'a Display,
     view: &'a TextureView,
+    format: TextureFormat,
+    depth: bool,
     encoder: &'a mut CommandEncoder,
     color: Color,
 ) -> RenderPass<'a> {
+    let depth_buffer = display.depth.as_ref().filter(|_| depth);
     let rpass_descriptor = wgpu::RenderPassDescriptor {
         label: None,
-        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-            view,
-            resolve_target: None,
-            ops: wgpu::Operations {
-                load: wgpu::LoadOp::Clear(color),
-                store: wgpu::StoreOp::Store,
+        color_attachments: &[Some(match &display.msaa {
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                // Formats the MSAA target can't be viewed in are left for wgpu to reject
+                view: msaa.view_as(format).unwrap_or(&msaa.view),
+                resolve_target: Some(view),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(color),
+                    store: wgpu::StoreOp::Discard,
+                },
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(color),
+                    store: wgpu::StoreOp::Store,
+                },
             },
         })],
-        depth_stencil_attachment: None,
+        depth_stencil_attachment: depth_buffer.map(|depth_buffer| {
+            wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_buffer.view,
+                depth_ops: depth_buffer
+                    .format()
+                    .has_depth_aspect()
+                    .then_some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                stencil_ops: depth_buffer.format().has_stencil_aspect().then_some(
+                    wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: wgpu::StoreOp::Store,
+                    },
+                ),
+            }
+        }),
         timestamp_writes: Default::default(),
         occlusion_query_set: Default::default(),
     };
@@ -51,8 +88,13 @@ pub fn create_shader(device: &Device, shader_source: &'static str) -> ShaderModu
     })
 }
 
+/// Creates a pipeline drawing into views of `display` in `format`, with the display's sample
+/// count. If `depth` is set and the display owns a depth buffer, depth testing is enabled, and
+/// the pipeline has to be used in passes that [`clear`] with `depth` set as well.
 pub fn create_render_pipeline<'a>(
     display: &Display,
+    format: TextureFormat,
+    depth: bool,
     pipeline_layout: PipelineLayout,
     shader: ShaderModule,
     buffers: &[VertexBufferLayout<'a>],
@@ -71,7 +113,7 @@ pub fn create_render_pipeline<'a>(
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
-                targets: &[Some(display.config.format.into())],
+                targets: &[Some(format.into())],
                 compilation_options: Default::default(),
             }),
             primitive: PrimitiveState {
@@ -83,8 +125,21 @@ pub fn create_render_pipeline<'a>(
                 unclipped_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: display
+                .depth
+                .as_ref()
+                .filter(|_| depth)
+                .map(|depth_buffer| wgpu::DepthStencilState {
+                    format: depth_buffer.format(),
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+            multisample: wgpu::MultisampleState {
+                count: display.sample_count(),
+                ..Default::default()
+            },
             multiview: None,
         })
 }
@@ -95,7 +150,7 @@ pub fn create_texture_layout_entry_from_image(
     image_bytes: &'static [u8],
     binding_index: u32,
 ) -> (BindGroupLayoutEntry, TextureView) {
-    let img = image::load(std::io::Cursor::new(image_bytes.as_ref()), ImageFormat::Png).unwrap();
+    let img = image::load(std::io::Cursor::new(image_bytes), ImageFormat::Png).unwrap();
     let rgba = img.to_rgba8();
     let dimensions = img.dimensions();
 
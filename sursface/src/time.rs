@@ -1,11 +1,190 @@
 use lazy_static::lazy_static;
+use web_time::Instant;
 
 lazy_static! {
     static ref START_TIME: web_time::Instant = web_time::Instant::now();
 }
 
+/// Seconds since the first call. Loses precision over long sessions, prefer the
+/// [`FrameClock`] passed to `draw` and `update`.
 pub fn now_secs() -> f32 {
     web_time::Instant::now()
         .duration_since(*START_TIME)
         .as_secs_f64() as f32
 }
+
+/// Rate at which [`AppState::update`](crate::app::AppState::update) runs, independently of the
+/// frame rate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedTimestep {
+    /// Updates per second.
+    pub tick_rate: f64,
+    /// Most updates run before a single frame. Time beyond that is dropped, so a long stall
+    /// slows the simulation down instead of freezing the app while it catches up.
+    pub max_steps: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self {
+            tick_rate: 60.0,
+            max_steps: 5,
+        }
+    }
+}
+
+impl FixedTimestep {
+    pub fn with_tick_rate(mut self, tick_rate: f64) -> Self {
+        self.tick_rate = tick_rate;
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Seconds simulated by a single update.
+    pub fn dt(&self) -> f64 {
+        1.0 / self.tick_rate
+    }
+}
+
+/// Timing of the app's frames, passed to [`AppState::update`](crate::app::AppState::update) and
+/// [`AppState::draw`](crate::app::AppState::draw).
+///
+/// There is one clock for the whole app, advanced when the main window draws. Other windows
+/// drawn in between see the same values.
+///
+/// Pausing and the time scale apply to the total time, the frame delta and the fixed updates,
+/// while the frame rate is always measured in real time.
+pub struct FrameClock {
+    timestep: FixedTimestep,
+    last_frame: Option<Instant>,
+    total: f64,
+    delta: f64,
+    fps: f64,
+    frame: u64,
+    paused: bool,
+    time_scale: f64,
+    /// Scaled time not yet consumed by fixed updates.
+    lag: f64,
+}
+
+impl FrameClock {
+    pub(crate) fn new(timestep: FixedTimestep) -> Self {
+        Self {
+            timestep,
+            last_frame: None,
+            total: 0.0,
+            delta: 0.0,
+            fps: 0.0,
+            frame: 0,
+            paused: false,
+            time_scale: 1.0,
+            lag: 0.0,
+        }
+    }
+
+    /// Starts a new frame and returns how many fixed updates are due before it is drawn.
+    pub(crate) fn tick(&mut self, now: Instant) -> u32 {
+        let real_delta = self
+            .last_frame
+            .map_or(0.0, |last| now.duration_since(last).as_secs_f64());
+        self.last_frame = Some(now);
+        self.frame += 1;
+
+        if real_delta > 0.0 {
+            let fps = 1.0 / real_delta;
+            self.fps = if self.fps == 0.0 {
+                fps
+            } else {
+                self.fps * 0.9 + fps * 0.1
+            };
+        }
+
+        self.delta = if self.paused {
+            0.0
+        } else {
+            real_delta * self.time_scale
+        };
+        self.total += self.delta;
+        self.lag += self.delta;
+
+        let dt = self.timestep.dt();
+        let mut steps = 0;
+        while self.lag >= dt && steps < self.timestep.max_steps {
+            self.lag -= dt;
+            steps += 1;
+        }
+        if self.lag >= dt {
+            log::trace!(
+                "Dropping {:.3}s of simulation time",
+                self.lag - self.lag % dt
+            );
+            self.lag %= dt;
+        }
+
+        steps
+    }
+
+    /// Forgets the time since the last frame, e.g. after the app was suspended.
+    pub(crate) fn reset(&mut self) {
+        self.last_frame = None;
+        self.lag = 0.0;
+    }
+
+    /// Seconds elapsed while not paused, scaled by the time scale.
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+
+    /// Seconds since the previous frame, scaled by the time scale and 0 while paused.
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    /// Frames per second, smoothed over the last few frames.
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Number of frames the main window drew so far, counting from 1 for the first one.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Seconds simulated by each fixed update, see [`AppState::timestep`](crate::app::AppState::timestep).
+    pub fn fixed_dt(&self) -> f64 {
+        self.timestep.dt()
+    }
+
+    /// How far the current frame is between the last fixed update and the next one, in
+    /// `[0, 1)`, for interpolating between simulation states.
+    pub fn alpha(&self) -> f64 {
+        self.lag / self.timestep.dt()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops the total time and the fixed updates. Frames are still drawn.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Makes time pass `time_scale` times as fast, e.g. 0.25 for slow motion. Fixed updates keep
+    /// their step and only run less often.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.time_scale = time_scale.max(0.0);
+    }
+}